@@ -1,24 +1,777 @@
-use crate::{entities::{Health}, events::AttackEvent, GameState, InGameState};
-use bevy::prelude::*;
+use crate::{
+    entities::{
+        Armor, AttackCollider, AttackDirection, Blast, Corpse, Dead, DoTStacking, DoTStat, DoTType,
+        Faction, Hazard, HazardDamage, Health, HitBox, HitInvulnerability, Impulse, Invulnerable,
+        LifeSteal, Projectile, RangedWeapon, RunEntity, Shield, StatModifiers, StatType, Strength,
+        Stunned, Weapon,
+    },
+    events::{AttackEvent, BounceEvent, DamageEvent, DeathEvent},
+    GameState, InGameState,
+};
+use bevy::{math::Vec2Swizzles, prelude::*, utils::Duration, utils::HashMap};
+use bevy_rapier2d::prelude::*;
+
+// minimum damage for any hit, so armor never makes an entity immortal
+const MIN_DAMAGE: i32 = 1;
+const MAX_DOT_STACKS: u8 = 5;
+// part of knockback that lifts the target up
+const KNOCKBACK_LIFT: f32 = 0.5;
+const BLINK_MILLIS: u128 = 100;
 
 // plugin for damage, debuff, buff and other
-// pub struct CombatPlugin;
-
-// impl Plugin for CombatPlugin {
-//     fn build(&self, app: &mut App) {
-//         app.add_systems(
-//             Update,
-//             (damage_hit, debuff_effect)
-//                 .run_if(in_state(GameState::InGame))
-//                 .run_if(in_state(InGameState::Play)),
-//         );
-//     }
-// }
-
-// fn damage_hit(mut cmd: Commands, mut query: Query<&mut Health/*Speed */>, event: EventReader<AttackEvent>) {
-//     println!("123123123123123")
-// }
-
-// fn debuff_effect(hp: Query<&mut Health/*Speed */>, debuff: Query<>) {
-//     println!("asdasdasdasd")
-// }
\ No newline at end of file
+pub struct CombatPlugin;
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DeathSettings>()
+            .init_resource::<FriendlyFire>()
+            .add_event::<AttackEvent>()
+            .add_event::<DamageEvent>()
+            .add_event::<DeathEvent>()
+            .add_event::<BounceEvent>()
+            .add_systems(
+                Update,
+                (
+                    (
+                        hit_detection,
+                        projectile_hits,
+                        blast_hits,
+                        hazard_damage,
+                        damage_hit,
+                        tick_dots,
+                        apply_damage,
+                        on_death,
+                    )
+                        .chain(),
+                    despawn_corpses,
+                    tick_hitboxes,
+                    tick_blasts,
+                    move_projectiles,
+                    tick_modifiers,
+                    tick_stun,
+                    tick_invulnerable,
+                )
+                    .run_if(in_state(GameState::InGame))
+                    .run_if(in_state(InGameState::Play)),
+            );
+    }
+}
+
+// delays between death and removal of the dead entity
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct DeathSettings {
+    pub corpse_time: Duration,
+    pub game_over_time: Duration,
+}
+
+impl Default for DeathSettings {
+    fn default() -> Self {
+        Self {
+            corpse_time: Duration::from_secs(2),
+            game_over_time: Duration::from_millis(1500),
+        }
+    }
+}
+
+// attacks hit entities of the same faction, enemies hurt each other with swings and arrows
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct FriendlyFire(pub bool);
+
+// Strength * attack damage = true damage
+pub fn true_damage(strength: i32, damage: isize) -> i32 {
+    (strength as isize * damage) as i32
+}
+
+// armor is subtracted from the true damage
+pub fn armor_reduction(damage: i32, armor: i32) -> i32 {
+    let damage = damage - armor;
+    damage.max(MIN_DAMAGE)
+}
+
+// spawn sensor of the weapon as child of the owner, rotation - look direction of the owner
+// up and down attacks turn the sensor of the weapon by 90 degrees
+pub fn spawn_hitbox(
+    commands: &mut Commands,
+    owner: Entity,
+    weapon: &Weapon,
+    rotation: i8,
+    direction: AttackDirection,
+) -> Entity {
+    let (position, size) = match direction {
+        AttackDirection::Side => (
+            Vec2::new(rotation as f32 * weapon.offset.x, weapon.offset.y),
+            weapon.hitbox,
+        ),
+        AttackDirection::Up => (Vec2::new(0., weapon.offset.x), weapon.hitbox.yx()),
+        AttackDirection::Down => (Vec2::new(0., -weapon.offset.x), weapon.hitbox.yx()),
+    };
+    let hitbox = commands
+        .spawn((
+            TransformBundle::from_transform(Transform::from_translation(position.extend(0.))),
+            Collider::cuboid(size.x, size.y),
+            Sensor,
+            ActiveEvents::COLLISION_EVENTS,
+            // fixed colliders for hazards, terrain is skipped by hit_detection
+            ActiveCollisionTypes::default()
+                | ActiveCollisionTypes::KINEMATIC_KINEMATIC
+                | ActiveCollisionTypes::KINEMATIC_STATIC,
+            HitBox {
+                damage: weapon.damage.clone(),
+                effects: weapon.effects.clone(),
+                hit: Vec::new(),
+                lifetime: Timer::new(weapon.attack_time, TimerMode::Once),
+                interrupt: weapon.interrupt,
+                knockback: weapon.knockback,
+                direction,
+            },
+        ))
+        .id();
+    commands.entity(owner).add_child(hitbox);
+    hitbox
+}
+
+// spawn sensor of the weapon at the position, it is not attached to the owner
+// and stays when the owner is gone
+pub fn spawn_blast(
+    commands: &mut Commands,
+    owner: Entity,
+    faction: Faction,
+    weapon: &Weapon,
+    position: Vec2,
+) -> Entity {
+    commands
+        .spawn((
+            TransformBundle::from_transform(Transform::from_translation(position.extend(0.))),
+            Collider::cuboid(weapon.hitbox.x, weapon.hitbox.y),
+            Sensor,
+            ActiveEvents::COLLISION_EVENTS,
+            // the sensor has no body, kinematic characters are hit as a fixed collider
+            ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_STATIC,
+            Blast {
+                owner,
+                faction,
+                damage: weapon.damage.clone(),
+                effects: weapon.effects.clone(),
+                hit: Vec::new(),
+                lifetime: Timer::new(weapon.attack_time, TimerMode::Once),
+                interrupt: weapon.interrupt,
+                knockback: weapon.knockback,
+            },
+            RunEntity,
+        ))
+        .id()
+}
+
+// fire the projectile of the ranged weapon from origin to target
+// projectile with gravity is aimed higher to fall on the target
+pub fn spawn_projectile(
+    commands: &mut Commands,
+    owner: Entity,
+    faction: Faction,
+    weapon: &RangedWeapon,
+    origin: Vec2,
+    target: Vec2,
+    gravity: Vec2,
+) -> Entity {
+    let to_target = target - origin;
+    let flight_time = to_target.length() / weapon.speed;
+    let velocity =
+        to_target.normalize_or_zero() * weapon.speed - gravity * weapon.gravity * flight_time * 0.5;
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: weapon.color,
+                    custom_size: Some(weapon.size * 2.),
+                    ..default()
+                },
+                transform: Transform::from_translation(origin.extend(10.))
+                    .with_rotation(Quat::from_rotation_z(velocity.y.atan2(velocity.x))),
+                ..default()
+            },
+            RigidBody::KinematicPositionBased,
+            Collider::cuboid(weapon.size.x, weapon.size.y),
+            Sensor,
+            ActiveEvents::COLLISION_EVENTS,
+            ActiveCollisionTypes::default()
+                | ActiveCollisionTypes::KINEMATIC_KINEMATIC
+                | ActiveCollisionTypes::KINEMATIC_STATIC,
+            Projectile {
+                owner,
+                faction,
+                damage: weapon.damage.clone(),
+                effects: weapon.effects.clone(),
+                interrupt: weapon.interrupt,
+                knockback: weapon.knockback,
+                velocity,
+                gravity: weapon.gravity,
+                lifetime: Timer::new(weapon.lifetime, TimerMode::Once),
+            },
+            RunEntity,
+        ))
+        .id()
+}
+
+fn move_projectiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    rapier_config: Res<RapierConfiguration>,
+    mut projectile_q: Query<(Entity, &mut Projectile, &mut Transform)>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut projectile, mut transform) in projectile_q.iter_mut() {
+        projectile.lifetime.tick(time.delta());
+        if projectile.lifetime.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let gravity = rapier_config.gravity * projectile.gravity;
+        projectile.velocity += gravity * dt;
+        transform.translation += (projectile.velocity * dt).extend(0.);
+        transform.rotation =
+            Quat::from_rotation_z(projectile.velocity.y.atan2(projectile.velocity.x));
+    }
+}
+
+// remove sensor when the swing is over
+fn tick_hitboxes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut hitbox_q: Query<(Entity, &Parent, &mut HitBox)>,
+    mut owner_q: Query<&mut AttackCollider>,
+) {
+    for (entity, owner, mut hitbox) in hitbox_q.iter_mut() {
+        hitbox.lifetime.tick(time.delta());
+        if !hitbox.lifetime.finished() {
+            continue;
+        }
+        if let Ok(mut attack_collider) = owner_q.get_mut(owner.get()) {
+            if attack_collider.0 == Some(entity) {
+                attack_collider.0 = None;
+            }
+        }
+        commands.entity(owner.get()).remove_children(&[entity]);
+        commands.entity(entity).despawn();
+    }
+}
+
+// remove blast when its lifetime is over
+fn tick_blasts(mut commands: Commands, time: Res<Time>, mut blast_q: Query<(Entity, &mut Blast)>) {
+    for (entity, mut blast) in blast_q.iter_mut() {
+        blast.lifetime.tick(time.delta());
+        if blast.lifetime.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+// resolve collisions of attack sensors into "attacker hit target" events
+fn hit_detection(
+    friendly_fire: Res<FriendlyFire>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut hitbox_q: Query<(&Parent, &mut HitBox)>,
+    targets: Query<Option<&Faction>, With<Health>>,
+    hazards: Query<(), With<Hazard>>,
+    mut attack_events: EventWriter<AttackEvent>,
+    mut bounce_events: EventWriter<BounceEvent>,
+) {
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(first, second, _) = collision_event else {
+            continue;
+        };
+        for (sensor, target) in [(*first, *second), (*second, *first)] {
+            let Ok((owner, mut hitbox)) = hitbox_q.get_mut(sensor) else {
+                continue;
+            };
+            let attacker = owner.get();
+            // self hit, terrain or target already hit by this swing
+            if target == attacker || hitbox.hit.contains(&target) {
+                continue;
+            }
+            if hitbox.direction == AttackDirection::Down && hazards.contains(target) {
+                hitbox.hit.push(target);
+                bounce_events.send(BounceEvent { entity: attacker });
+                continue;
+            }
+            let Ok(target_faction) = targets.get(target) else {
+                continue;
+            };
+            if let (Some(target_faction), Ok(Some(attacker_faction))) =
+                (target_faction, targets.get(attacker))
+            {
+                if target_faction == attacker_faction && !friendly_fire.0 {
+                    continue;
+                }
+            }
+            hitbox.hit.push(target);
+            if hitbox.direction == AttackDirection::Down {
+                bounce_events.send(BounceEvent { entity: attacker });
+            }
+            attack_events.send(AttackEvent {
+                attacker,
+                target,
+                damage: hitbox.damage.0,
+                effects: hitbox.effects.clone(),
+                interrupt: hitbox.interrupt,
+                knockback: hitbox.knockback,
+            });
+        }
+    }
+}
+
+// projectile hits the first target of another faction, terrain stops it
+fn projectile_hits(
+    mut commands: Commands,
+    friendly_fire: Res<FriendlyFire>,
+    mut collision_events: EventReader<CollisionEvent>,
+    projectile_q: Query<&Projectile>,
+    targets: Query<Option<&Faction>, With<Health>>,
+    terrain: Query<&RigidBody>,
+    mut attack_events: EventWriter<AttackEvent>,
+    mut spent: Local<Vec<Entity>>,
+) {
+    spent.clear();
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(first, second, _) = collision_event else {
+            continue;
+        };
+        for (sensor, target) in [(*first, *second), (*second, *first)] {
+            let Ok(projectile) = projectile_q.get(sensor) else {
+                continue;
+            };
+            // projectile hit two colliders in the same frame
+            if spent.contains(&sensor) || target == projectile.owner {
+                continue;
+            }
+            match targets.get(target) {
+                Ok(Some(faction)) if *faction == projectile.faction && !friendly_fire.0 => continue,
+                Ok(_) => attack_events.send(AttackEvent {
+                    attacker: projectile.owner,
+                    target,
+                    damage: projectile.damage.0,
+                    effects: projectile.effects.clone(),
+                    interrupt: projectile.interrupt,
+                    knockback: projectile.knockback,
+                }),
+                Err(_) if matches!(terrain.get(target), Ok(RigidBody::Fixed)) => (),
+                Err(_) => continue,
+            }
+            spent.push(sensor);
+            commands.entity(sensor).despawn_recursive();
+        }
+    }
+}
+
+// blast hits every target of another faction in the area once
+fn blast_hits(
+    friendly_fire: Res<FriendlyFire>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut blast_q: Query<&mut Blast>,
+    targets: Query<Option<&Faction>, With<Health>>,
+    mut attack_events: EventWriter<AttackEvent>,
+) {
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(first, second, _) = collision_event else {
+            continue;
+        };
+        for (sensor, target) in [(*first, *second), (*second, *first)] {
+            let Ok(mut blast) = blast_q.get_mut(sensor) else {
+                continue;
+            };
+            if target == blast.owner || blast.hit.contains(&target) {
+                continue;
+            }
+            match targets.get(target) {
+                Ok(Some(faction)) if *faction == blast.faction && !friendly_fire.0 => continue,
+                Ok(_) => (),
+                Err(_) => continue,
+            }
+            blast.hit.push(target);
+            attack_events.send(AttackEvent {
+                attacker: blast.owner,
+                target,
+                damage: blast.damage.0,
+                effects: blast.effects.clone(),
+                interrupt: blast.interrupt,
+                knockback: blast.knockback,
+            });
+        }
+    }
+}
+
+// weapon damage of the hit scaled by attacker strength and modifiers
+// hazards hurt whoever is inside and throw them up, armor still applies
+fn hazard_damage(
+    time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    mut hazard_q: Query<(Entity, &mut HazardDamage)>,
+    targets: Query<(), (With<Health>, Without<Dead>)>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for (hazard, mut hazard_damage) in hazard_q.iter_mut() {
+        hazard_damage.cooldown.tick(time.delta());
+        if !hazard_damage.cooldown.finished() {
+            continue;
+        }
+        let mut hit = false;
+        for (first, second, intersecting) in rapier_context.intersections_with(hazard) {
+            let target = if first == hazard { second } else { first };
+            if !intersecting || !targets.contains(target) {
+                continue;
+            }
+            hit = true;
+            damage_events.send(DamageEvent {
+                source: Some(hazard),
+                direct: true,
+                target,
+                damage: hazard_damage.damage,
+                effects: Vec::new(),
+                interrupt: 0,
+                knockback: Vec2::Y * hazard_damage.knockback,
+            });
+        }
+        if hit {
+            hazard_damage.cooldown.reset();
+        }
+    }
+}
+
+fn damage_hit(
+    mut attack_events: EventReader<AttackEvent>,
+    attackers: Query<(&Strength, Option<&StatModifiers>)>,
+    transform_q: Query<&GlobalTransform>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for event in attack_events.iter() {
+        let (strength, weapon_damage) = match attackers.get(event.attacker) {
+            Ok((strength, Some(modifiers))) => (
+                strength.0,
+                modifiers
+                    .apply(StatType::AttackDamage, event.damage as f32)
+                    .round() as isize,
+            ),
+            Ok((strength, None)) => (strength.0, event.damage),
+            Err(_) => (1, event.damage),
+        };
+        let effects = event
+            .effects
+            .iter()
+            .cloned()
+            .map(|mut dot| {
+                dot.source = Some(event.attacker);
+                dot
+            })
+            .collect();
+
+        // push target away from the attacker
+        let mut knockback = Vec2::ZERO;
+        if let Ok([attacker_pos, target_pos]) = transform_q.get_many([event.attacker, event.target])
+        {
+            let direction = if target_pos.translation().x < attacker_pos.translation().x {
+                -1.
+            } else {
+                1.
+            };
+            knockback = Vec2::new(direction, KNOCKBACK_LIFT) * event.knockback;
+        }
+
+        damage_events.send(DamageEvent {
+            source: Some(event.attacker),
+            direct: true,
+            target: event.target,
+            damage: true_damage(strength, weapon_damage),
+            effects,
+            interrupt: event.interrupt,
+            knockback,
+        });
+    }
+}
+
+fn tick_dots(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut dot_q: Query<(Entity, &Parent, &mut DoTStat)>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for (entity, target, mut dot) in dot_q.iter_mut() {
+        dot.time.tick(time.delta());
+        dot.tick_dot.tick(time.delta());
+        for _ in 0..dot.tick_dot.times_finished_this_tick() {
+            damage_events.send(DamageEvent {
+                source: dot.source,
+                direct: false,
+                target: target.get(),
+                damage: dot.damage.0 as i32 * dot.stacks as i32,
+                effects: Vec::new(),
+                interrupt: 0,
+                knockback: Vec2::ZERO,
+            });
+        }
+        if dot.time.finished() {
+            commands.entity(target.get()).remove_children(&[entity]);
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn apply_damage(
+    mut commands: Commands,
+    mut damage_events: EventReader<DamageEvent>,
+    mut targets: Query<
+        (
+            &mut Health,
+            Option<&Armor>,
+            Option<&Children>,
+            Option<&mut Impulse>,
+            Option<&HitInvulnerability>,
+            Option<&mut Shield>,
+        ),
+        (Without<Dead>, Without<Invulnerable>),
+    >,
+    life_steal_q: Query<&LifeSteal>,
+    mut dot_q: Query<&mut DoTStat>,
+    mut death_events: EventWriter<DeathEvent>,
+) {
+    let mut heals = Vec::new();
+    // DoTs attached in this frame, the spawned entities are not in dot_q yet
+    let mut new_dots: HashMap<(Entity, DoTType), DoTStat> = HashMap::default();
+    for event in damage_events.iter() {
+        let Ok((mut health, armor, children, impulse, hit_invulnerability, shield)) =
+            targets.get_mut(event.target)
+        else {
+            continue;
+        };
+        let armor = match armor {
+            Some(armor) => armor.0,
+            None => 0,
+        };
+
+        let mut damage = armor_reduction(event.damage, armor);
+        if let Some(mut shield) = shield {
+            let absorbed = damage.min(shield.0);
+            shield.0 -= absorbed;
+            damage -= absorbed;
+        }
+        let was_alive = health.0 > 0;
+        health.0 -= damage;
+        info!(
+            "{:?} hit {:?} for {} damage, health left {}",
+            event.source, event.target, damage, health.0
+        );
+        // vampiric attacker heals from the dealt damage, after all hits of the frame
+        if let (true, Some(source)) = (event.direct, event.source) {
+            if let Ok(steal) = life_steal_q.get(source) {
+                let heal = (damage as f32 * steal.part).round() as i32;
+                heals.push((source, heal, steal.max_health));
+            }
+        }
+        if was_alive && health.0 <= 0 {
+            death_events.send(DeathEvent {
+                entity: event.target,
+                killer: event.source,
+            });
+            continue;
+        }
+
+        if let (true, Some(invulnerability)) = (event.direct, hit_invulnerability) {
+            commands
+                .entity(event.target)
+                .insert(Invulnerable::new(invulnerability.0, true));
+        }
+        if let Some(mut impulse) = impulse {
+            impulse.0 += event.knockback;
+        }
+        if event.interrupt > 0 {
+            stun(&mut commands, event.target, event.interrupt);
+        }
+        for effect in event.effects.iter() {
+            attach_dot(
+                &mut commands,
+                event.target,
+                children,
+                &mut dot_q,
+                &mut new_dots,
+                effect,
+            );
+        }
+    }
+
+    for ((target, _), dot) in new_dots {
+        let dot = commands.spawn(dot).id();
+        commands.entity(target).add_child(dot);
+    }
+
+    for (source, heal, max_health) in heals {
+        if let Ok((mut health, ..)) = targets.get_mut(source) {
+            health.0 = (health.0 + heal).min(max_health);
+        }
+    }
+}
+
+// apply DoT to target following the stacking rule of the effect
+fn attach_dot(
+    commands: &mut Commands,
+    target: Entity,
+    children: Option<&Children>,
+    dot_q: &mut Query<&mut DoTStat>,
+    new_dots: &mut HashMap<(Entity, DoTType), DoTStat>,
+    effect: &DoTStat,
+) {
+    let current = children.and_then(|children| {
+        children
+            .iter()
+            .find(|child| match dot_q.get(**child) {
+                Ok(dot) => dot.effect_type == effect.effect_type,
+                Err(_) => false,
+            })
+            .copied()
+    });
+
+    let stacked = match current.and_then(|entity| dot_q.get_mut(entity).ok()) {
+        Some(mut dot) => stack_dot(&mut dot),
+        None => match new_dots.get_mut(&(target, effect.effect_type)) {
+            Some(dot) => stack_dot(dot),
+            None => {
+                new_dots.insert((target, effect.effect_type), effect.clone());
+                true
+            }
+        },
+    };
+    if !stacked {
+        return;
+    }
+
+    if effect.stuck > 0 {
+        stun(commands, target, effect.stuck);
+    }
+}
+
+// stacking rule for a repeated DoT, false if the new one is ignored
+fn stack_dot(dot: &mut DoTStat) -> bool {
+    match dot.stacking {
+        DoTStacking::Refresh => dot.time.reset(),
+        DoTStacking::Intensity => {
+            dot.stacks = (dot.stacks + 1).min(MAX_DOT_STACKS);
+            dot.time.reset();
+        }
+        DoTStacking::Ignore => return false,
+    }
+    true
+}
+
+// stun in milliseconds, new stun replaces the current one
+fn stun(commands: &mut Commands, target: Entity, stuck: isize) {
+    commands.entity(target).insert(Stunned(Timer::new(
+        Duration::from_millis(stuck as u64),
+        TimerMode::Once,
+    )));
+}
+
+// dead entities stop colliding and moving, state and despawn are handled by the owner plugin
+fn on_death(
+    mut commands: Commands,
+    mut death_events: EventReader<DeathEvent>,
+    mut attack_q: Query<&mut AttackCollider>,
+) {
+    for event in death_events.iter() {
+        info!("{:?} killed by {:?}", event.entity, event.killer);
+        if let Ok(mut attack_collider) = attack_q.get_mut(event.entity) {
+            if let Some(collider_entity) = attack_collider.0.take() {
+                commands
+                    .entity(event.entity)
+                    .remove_children(&[collider_entity]);
+                commands.entity(collider_entity).despawn_recursive();
+            }
+        }
+        commands.entity(event.entity).insert(Dead).remove::<(
+            Collider,
+            KinematicCharacterController,
+            KinematicCharacterControllerOutput,
+        )>();
+    }
+}
+
+fn despawn_corpses(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut corpse_q: Query<(Entity, &mut Corpse)>,
+) {
+    for (entity, mut corpse) in corpse_q.iter_mut() {
+        corpse.0.tick(time.delta());
+        if corpse.0.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+// tick timed buffs and debuffs, remove expired ones
+fn tick_modifiers(time: Res<Time>, mut modifiers_q: Query<&mut StatModifiers>) {
+    for mut modifiers in modifiers_q.iter_mut() {
+        modifiers.tick(time.delta());
+    }
+}
+
+fn tick_stun(mut commands: Commands, time: Res<Time>, mut stun_q: Query<(Entity, &mut Stunned)>) {
+    for (entity, mut stun) in stun_q.iter_mut() {
+        stun.0.tick(time.delta());
+        if stun.0.finished() {
+            commands.entity(entity).remove::<Stunned>();
+        }
+    }
+}
+
+fn tick_invulnerable(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut invulnerable_q: Query<(Entity, &mut Invulnerable, Option<&mut Visibility>)>,
+) {
+    for (entity, mut invulnerable, visibility) in invulnerable_q.iter_mut() {
+        invulnerable.timer.tick(time.delta());
+        let finished = invulnerable.timer.finished();
+        if let Some(mut visibility) = visibility {
+            let hidden = (invulnerable.timer.elapsed().as_millis() / BLINK_MILLIS) % 2 == 1;
+            *visibility = if invulnerable.blink && hidden && !finished {
+                Visibility::Hidden
+            } else {
+                Visibility::Inherited
+            };
+        }
+        if finished {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn armor_is_subtracted() {
+        assert_eq!(armor_reduction(20, 14), 6);
+        assert_eq!(armor_reduction(20, 0), 20);
+    }
+
+    #[test]
+    fn armor_never_blocks_the_whole_hit() {
+        assert_eq!(armor_reduction(5, 14), MIN_DAMAGE);
+        assert_eq!(armor_reduction(0, 0), MIN_DAMAGE);
+    }
+
+    #[test]
+    fn negative_armor_adds_damage() {
+        assert_eq!(armor_reduction(10, -5), 15);
+    }
+
+    #[test]
+    fn intensity_dot_stacks_up_to_the_cap() {
+        let mut poison = DoTStat::poison();
+        for _ in 0..MAX_DOT_STACKS + 2 {
+            assert!(stack_dot(&mut poison));
+        }
+        assert_eq!(poison.stacks, MAX_DOT_STACKS);
+        assert!(!stack_dot(&mut DoTStat::bleed()));
+    }
+}
@@ -0,0 +1,416 @@
+use crate::combat::{spawn_hitbox, spawn_projectile};
+use crate::enemy::boss::Boss;
+use crate::enemy::flying::FlyingAi;
+use crate::enemy::group::{Attackers, GroupSettings};
+use crate::enemy::*;
+use crate::entities::*;
+use crate::map::nav::{NavGraph, NavPath};
+use crate::motor::{CharacterMotor, MotorSet};
+use crate::player::Player;
+use crate::{GameState, InGameState};
+
+use bevy::{prelude::*, utils::Duration};
+use bevy_rapier2d::prelude::*;
+
+pub struct EnemyAiPlugin;
+
+impl Plugin for EnemyAiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                enemy_senses.in_set(MotorSet::Input),
+                enemy_ai.in_set(MotorSet::Input),
+                enemy_attack,
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame))
+                .run_if(in_state(InGameState::Play)),
+        );
+    }
+}
+
+// movement of the enemy, enemy stops at attack_distance - attack_offset from the player
+// attack_offset keeps the enemy inside the attack range while the player moves
+// jump_impulse - jump while chasing, 0. for walking enemies, friction is set on the CharacterMotor
+// keep_distance - ranged enemy steps back when the player is closer, 0. for melee
+// vision_range and vision_angle (half of the cone in degrees) - enemy notices the player
+// leash_distance - max distance from the post while chasing, lose_interest - time without sight
+// patrol_distance - walk back and forth around the post, 0. to wait idle
+#[derive(Clone, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct EnemyAi {
+    pub attack_distance: f32,
+    pub attack_offset: f32,
+    pub jump_impulse: f32,
+    pub keep_distance: f32,
+    pub vision_range: f32,
+    pub vision_angle: f32,
+    pub leash_distance: f32,
+    pub lose_interest: Duration,
+    pub patrol_distance: f32,
+}
+
+impl EnemyAi {
+    fn stop_distance(&self) -> f32 {
+        (self.attack_distance - self.attack_offset).max(0.)
+    }
+}
+
+impl Default for EnemyAi {
+    fn default() -> Self {
+        Self {
+            attack_distance: 0.,
+            attack_offset: 0.,
+            jump_impulse: 0.,
+            keep_distance: 0.,
+            vision_range: 600.,
+            vision_angle: 60.,
+            leash_distance: 1200.,
+            lose_interest: Duration::from_secs(3),
+            patrol_distance: 0.,
+        }
+    }
+}
+
+// what the enemy knows: its post (spawn position) and whether it chases the player
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct AiMemory {
+    pub post: Option<Vec2>,
+    pub aggro: bool,
+    pub lost_sight: Timer,
+}
+
+// distance to the post to count as returned
+pub(super) const POST_REACHED: f32 = 10.;
+// time between path searches of the walking enemy
+pub const REPATH_TIME: Duration = Duration::from_millis(500);
+
+// player is in the vision cone and not behind terrain
+fn can_see(
+    rapier_context: &RapierContext,
+    ai: &EnemyAi,
+    aggro: bool,
+    rotation: i8,
+    position: Vec2,
+    target: Vec2,
+) -> bool {
+    let to_target = target - position;
+    let distance = to_target.length();
+    if distance > ai.vision_range || distance == 0. {
+        return false;
+    }
+    // chasing enemy tracks the player around
+    // rotation 0 is not set yet, the enemy looks right
+    let facing = if rotation < 0 { Vec2::NEG_X } else { Vec2::X };
+    if !aggro && facing.angle_between(to_target).abs() > ai.vision_angle.to_radians() {
+        return false;
+    }
+    rapier_context
+        .cast_ray(
+            position,
+            to_target / distance,
+            distance,
+            true,
+            QueryFilter::only_fixed(),
+        )
+        .is_none()
+}
+
+// melee attack with the equipped weapon
+// range - distance to the player to start the attack, windup - delay before the hitbox,
+// cooldown - recover time before the next attack
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct EnemyAttack {
+    range: Vec2,
+    windup: Timer,
+    cooldown: Timer,
+}
+
+impl EnemyAttack {
+    pub fn new(range: Vec2, windup: Duration, cooldown: Duration) -> Self {
+        let mut cooldown = Timer::new(cooldown, TimerMode::Once);
+        cooldown.tick(cooldown.duration()); // ready on spawn
+        Self {
+            range,
+            windup: Timer::new(windup, TimerMode::Once),
+            cooldown,
+        }
+    }
+}
+
+// aggro of walking and flying enemies, returning enemy ignores the player until it is back on the post
+// boss keeps the aggro while its arena is closed
+pub(super) fn enemy_senses(
+    time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    mut enemy_q: Query<
+        (
+            &EnemyAi,
+            &mut AiMemory,
+            &mut ActiveEntity<EnemyStates>,
+            &Transform,
+            Option<&Boss>,
+        ),
+        (With<Enemy>, Without<Dead>),
+    >,
+    player_q: Query<&Transform, (With<Player>, Without<Dead>)>,
+) {
+    let player_pos = player_q.get_single().ok().map(|t| t.translation.truncate());
+    for (ai, mut memory, mut enemy, enemy_pos, boss) in enemy_q.iter_mut() {
+        let position = enemy_pos.translation.truncate();
+        let post = *memory.post.get_or_insert(position);
+        if boss.is_some_and(|boss| boss.fight) {
+            memory.aggro = true;
+            continue;
+        }
+
+        if enemy.current_state != EnemyStates::Return {
+            let sees_player = match player_pos {
+                Some(player_pos) => can_see(
+                    &rapier_context,
+                    ai,
+                    memory.aggro,
+                    enemy.rotation,
+                    position,
+                    player_pos,
+                ),
+                None => false,
+            };
+            if sees_player {
+                memory.aggro = true;
+                memory.lost_sight = Timer::new(ai.lose_interest, TimerMode::Once);
+            } else if memory.aggro {
+                memory.lost_sight.tick(time.delta());
+                if memory.lost_sight.finished() {
+                    memory.aggro = false;
+                    enemy.current_state = EnemyStates::Return;
+                }
+            }
+            if memory.aggro && position.distance(post) > ai.leash_distance {
+                memory.aggro = false;
+                enemy.current_state = EnemyStates::Return;
+            }
+        }
+    }
+}
+
+// state machine Idle/Patrol -> Run (player seen) -> Return (lost interest) -> Idle/Patrol
+// Atack is started and finished by enemy_attack
+// goal on another level (platform, pit) is reached by the path of the NavGraph,
+// enemies without jump_impulse only walk and drop, and stop on ledges
+fn enemy_ai(
+    time: Res<Time>,
+    nav: Option<Res<NavGraph>>,
+    mut enemy_q: Query<
+        (
+            &EnemyAi,
+            &AiMemory,
+            &mut ActiveEntity<EnemyStates>,
+            &mut CharacterMotor,
+            &mut NavPath,
+            &Transform,
+            &Speed,
+            &StatModifiers,
+            Option<&Stunned>,
+        ),
+        (With<Enemy>, Without<Dead>, Without<FlyingAi>),
+    >,
+    player_q: Query<&Transform, (With<Player>, Without<Dead>)>,
+) {
+    let player_pos = player_q.get_single().ok().map(|t| t.translation.truncate());
+    for (ai, memory, mut enemy, mut motor, mut path, enemy_pos, speed, modifiers, stun) in
+        enemy_q.iter_mut()
+    {
+        let position = enemy_pos.translation.truncate();
+        let post = memory.post.unwrap_or(position);
+        let speed = modifiers.apply(StatType::MoveSpeed, speed.0);
+        let can_jump = ai.jump_impulse > 0.;
+        let ledge_ahead = |direction: f32| match &nav {
+            Some(nav) if !can_jump => !nav.walkable_ahead(position, direction),
+            _ => false,
+        };
+
+        let mut direction = 0.;
+        if enemy.current_state != EnemyStates::Atack && stun.is_none() {
+            let goal = match (memory.aggro, player_pos) {
+                (true, Some(player_pos)) => Some(player_pos),
+                _ if enemy.current_state == EnemyStates::Return
+                    || enemy.current_state == EnemyStates::Run =>
+                {
+                    Some(post)
+                }
+                _ => None,
+            };
+            let step = match (&nav, goal) {
+                (Some(nav), Some(goal)) => path
+                    .next(nav, position, goal, can_jump, time.delta())
+                    .filter(|_| path.changes_level()),
+                _ => None,
+            };
+
+            match (memory.aggro, player_pos) {
+                _ if step.is_some() => {
+                    let distance = step.map_or(0., |s| s.position.x - position.x);
+                    enemy.current_state = if memory.aggro {
+                        EnemyStates::Run
+                    } else {
+                        EnemyStates::Return
+                    };
+                    enemy.rotation = if distance < 0. { -1 } else { 1 };
+                    direction = enemy.rotation as f32;
+                }
+                (true, Some(player_pos)) => {
+                    let distance = player_pos.x - position.x;
+                    enemy.rotation = if distance < 0. { -1 } else { 1 };
+                    if distance.abs() > ai.stop_distance() {
+                        enemy.current_state = EnemyStates::Run;
+                        direction = enemy.rotation as f32;
+                    } else if distance.abs() < ai.keep_distance {
+                        // step back still looking at the player
+                        enemy.current_state = EnemyStates::Run;
+                        direction = -enemy.rotation as f32;
+                    } else {
+                        enemy.current_state = EnemyStates::Idle;
+                    }
+                    // player is out of reach, wait on the edge
+                    if direction != 0. && ledge_ahead(direction) {
+                        enemy.current_state = EnemyStates::Idle;
+                        direction = 0.;
+                    }
+                }
+                _ if enemy.current_state == EnemyStates::Return
+                    || enemy.current_state == EnemyStates::Run =>
+                {
+                    let distance = post.x - position.x;
+                    if distance.abs() > POST_REACHED {
+                        enemy.current_state = EnemyStates::Return;
+                        enemy.rotation = if distance < 0. { -1 } else { 1 };
+                        direction = enemy.rotation as f32;
+                    } else {
+                        enemy.current_state = EnemyStates::Idle;
+                    }
+                }
+                _ if ai.patrol_distance > 0. => {
+                    // turn around on the edge of the patrol zone or the ledge
+                    let offset = position.x - post.x;
+                    if offset * enemy.rotation as f32 > ai.patrol_distance
+                        || ledge_ahead(enemy.rotation as f32)
+                    {
+                        enemy.rotation = -enemy.rotation;
+                    }
+                    enemy.current_state = EnemyStates::Patrol;
+                    direction = enemy.rotation as f32;
+                }
+                _ => enemy.current_state = EnemyStates::Idle,
+            }
+        }
+
+        if motor.grounded && direction != 0. && enemy.current_state == EnemyStates::Run {
+            motor.input.y += ai.jump_impulse;
+        }
+        motor.input.x += direction * speed;
+    }
+}
+
+// enemy with RangedWeapon fires a projectile at the player instead of the melee swing
+fn enemy_attack(
+    mut commands: Commands,
+    time: Res<Time>,
+    rapier_config: Res<RapierConfiguration>,
+    group: Res<GroupSettings>,
+    mut attackers: ResMut<Attackers>,
+    mut enemy_q: Query<
+        (
+            Entity,
+            &mut ActiveEntity<EnemyStates>,
+            &mut EnemyAttack,
+            &AiMemory,
+            &mut AttackCollider,
+            &Weapon,
+            Option<&RangedWeapon>,
+            &Faction,
+            &Transform,
+            Option<&Stunned>,
+        ),
+        (With<Enemy>, Without<Dead>),
+    >,
+    player_q: Query<&Transform, (With<Player>, Without<Dead>)>,
+) {
+    let player_pos = player_q.get_single().ok();
+    for (
+        entity,
+        mut enemy,
+        mut attack,
+        memory,
+        mut attack_collider,
+        weapon,
+        ranged,
+        faction,
+        enemy_pos,
+        stun,
+    ) in enemy_q.iter_mut()
+    {
+        attack.cooldown.tick(time.delta());
+
+        if enemy.current_state == EnemyStates::Atack {
+            // interrupted by a hit
+            if stun.is_some() {
+                enemy.current_state = EnemyStates::Idle;
+                continue;
+            }
+            attack.windup.tick(time.delta());
+            if attack.windup.just_finished() {
+                match (ranged, player_pos) {
+                    (Some(ranged), Some(player_pos)) => {
+                        spawn_projectile(
+                            &mut commands,
+                            entity,
+                            *faction,
+                            ranged,
+                            enemy_pos.translation.truncate(),
+                            player_pos.translation.truncate(),
+                            rapier_config.gravity,
+                        );
+                    }
+                    // player is dead, nobody to shoot at
+                    (Some(_), None) => (),
+                    (None, _) => {
+                        let hitbox = spawn_hitbox(
+                            &mut commands,
+                            entity,
+                            weapon,
+                            enemy.rotation,
+                            AttackDirection::Side,
+                        );
+                        attack_collider.0 = Some(hitbox);
+                    }
+                }
+                attack.cooldown.reset();
+            }
+            // recover when the swing is over
+            if attack.windup.finished() && attack_collider.0.is_none() {
+                enemy.current_state = EnemyStates::Idle;
+            }
+            continue;
+        }
+
+        let Some(player_pos) = player_pos else {
+            continue;
+        };
+        let distance = (player_pos.translation - enemy_pos.translation).truncate();
+        let in_range = distance.x.abs() <= attack.range.x && distance.y.abs() <= attack.range.y;
+        if memory.aggro
+            && stun.is_none()
+            && in_range
+            && attack.cooldown.finished()
+            && attackers.try_join(&group)
+        {
+            enemy.rotation = if distance.x < 0. { -1 } else { 1 };
+            enemy.current_state = EnemyStates::Atack;
+            attack.windup.reset();
+        }
+    }
+}
@@ -0,0 +1,425 @@
+use crate::enemy::ai::REPATH_TIME;
+use crate::enemy::boss::{Boss, BossPhase};
+use crate::enemy::flying::FlyingAi;
+use crate::enemy::*;
+use crate::entities::*;
+use crate::motor::CharacterMotor;
+use crate::weapon::WeaponDefinition;
+
+use bevy::reflect::{TypePath, TypeUuid};
+use bevy::{prelude::*, utils::Duration};
+use bevy_rapier2d::prelude::*;
+use serde::Deserialize;
+
+// enemy type loaded from assets/enemies/*.enemy.ron
+// cost - price in the difficulty budget of the spawn director, 0 - never picked by it
+// spawns - fixed positions in the first room, used for bosses
+#[derive(Deserialize, TypeUuid, TypePath, Debug, Clone)]
+#[uuid = "6f0a3c1e-2b7d-4e59-9a8c-3d51f2b4e7a1"]
+pub struct EnemyArchetype {
+    pub name: String,
+    pub stats: StatsDefinition,
+    pub collider: ColliderShape,
+    pub sprite: SpriteDefinition,
+    #[serde(default)]
+    pub ai: AiDefinition,
+    #[serde(default)]
+    pub motor: MotorDefinition,
+    #[serde(default)]
+    pub flying: Option<FlyingDefinition>,
+    #[serde(default)]
+    pub weapon: Option<WeaponDefinition>,
+    #[serde(default)]
+    pub attack: Option<AttackDefinition>,
+    #[serde(default)]
+    pub ranged: Option<RangedDefinition>,
+    #[serde(default)]
+    pub contact: Option<ContactDefinition>,
+    #[serde(default)]
+    pub loot: Vec<LootEntry>,
+    #[serde(default)]
+    pub boss: Option<BossDefinition>,
+    #[serde(default)]
+    pub cost: u32,
+    #[serde(default)]
+    pub spawns: Vec<(f32, f32)>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct StatsDefinition {
+    pub health: i32,
+    pub armor: i32,
+    pub strength: i32,
+    pub speed: f32,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub enum ColliderShape {
+    Cuboid { half_width: f32, half_height: f32 },
+    Ball { radius: f32 },
+    Capsule { half_height: f32, radius: f32 },
+}
+
+impl ColliderShape {
+    fn collider(&self) -> Collider {
+        match *self {
+            ColliderShape::Cuboid {
+                half_width,
+                half_height,
+            } => Collider::cuboid(half_width, half_height),
+            ColliderShape::Ball { radius } => Collider::ball(radius),
+            ColliderShape::Capsule {
+                half_height,
+                radius,
+            } => Collider::capsule_y(half_height, radius),
+        }
+    }
+
+    // distance from the center to the bottom of the shape
+    pub fn half_height(&self) -> f32 {
+        match *self {
+            ColliderShape::Cuboid { half_height, .. } => half_height,
+            ColliderShape::Ball { radius } => radius,
+            ColliderShape::Capsule {
+                half_height,
+                radius,
+            } => half_height + radius,
+        }
+    }
+}
+
+// texture - path in assets, without texture the sprite is a rectangle of the color
+#[derive(Deserialize, Debug, Clone)]
+pub struct SpriteDefinition {
+    pub color: String,
+    pub size: (f32, f32),
+    #[serde(default)]
+    pub texture: Option<String>,
+}
+
+// same fields as EnemyAi, time in milliseconds
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct AiDefinition {
+    pub attack_distance: f32,
+    pub attack_offset: f32,
+    pub jump_impulse: f32,
+    pub keep_distance: f32,
+    pub vision_range: f32,
+    pub vision_angle: f32,
+    pub leash_distance: f32,
+    pub lose_interest_ms: u64,
+    pub patrol_distance: f32,
+}
+
+impl Default for AiDefinition {
+    fn default() -> Self {
+        let ai = EnemyAi::default();
+        Self {
+            attack_distance: ai.attack_distance,
+            attack_offset: ai.attack_offset,
+            jump_impulse: ai.jump_impulse,
+            keep_distance: ai.keep_distance,
+            vision_range: ai.vision_range,
+            vision_angle: ai.vision_angle,
+            leash_distance: ai.leash_distance,
+            lose_interest_ms: ai.lose_interest.as_millis() as u64,
+            patrol_distance: ai.patrol_distance,
+        }
+    }
+}
+
+impl AiDefinition {
+    fn ai(&self) -> EnemyAi {
+        EnemyAi {
+            attack_distance: self.attack_distance,
+            attack_offset: self.attack_offset,
+            jump_impulse: self.jump_impulse,
+            keep_distance: self.keep_distance,
+            vision_range: self.vision_range,
+            vision_angle: self.vision_angle,
+            leash_distance: self.leash_distance,
+            lose_interest: Duration::from_millis(self.lose_interest_ms),
+            patrol_distance: self.patrol_distance,
+        }
+    }
+}
+
+// same fields as CharacterMotor, gravity_scale 0. for flying enemies
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct MotorDefinition {
+    pub friction: f32,
+    pub air_friction: f32,
+    pub gravity_scale: f32,
+}
+
+impl Default for MotorDefinition {
+    fn default() -> Self {
+        let motor = CharacterMotor::default();
+        Self {
+            friction: motor.friction,
+            air_friction: motor.air_friction,
+            gravity_scale: motor.gravity_scale,
+        }
+    }
+}
+
+impl MotorDefinition {
+    fn motor(&self) -> CharacterMotor {
+        CharacterMotor {
+            friction: self.friction,
+            air_friction: self.air_friction,
+            gravity_scale: self.gravity_scale,
+            ..default()
+        }
+    }
+}
+
+// same fields as FlyingAi, time in milliseconds
+#[derive(Deserialize, Debug, Clone)]
+pub struct FlyingDefinition {
+    pub hover_height: f32,
+    pub steering: f32,
+    pub avoid_distance: f32,
+    pub swoop_range: f32,
+    pub swoop_speed: f32,
+    pub swoop_ms: u64,
+    pub swoop_cooldown_ms: u64,
+}
+
+impl FlyingDefinition {
+    fn ai(&self) -> FlyingAi {
+        let mut cooldown = Timer::new(
+            Duration::from_millis(self.swoop_cooldown_ms),
+            TimerMode::Once,
+        );
+        cooldown.tick(cooldown.duration()); // ready on spawn
+        FlyingAi {
+            hover_height: self.hover_height,
+            steering: self.steering,
+            avoid_distance: self.avoid_distance,
+            swoop_range: self.swoop_range,
+            swoop_speed: self.swoop_speed,
+            swoop: Timer::new(Duration::from_millis(self.swoop_ms), TimerMode::Once),
+            cooldown,
+            swoop_target: Vec2::ZERO,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AttackDefinition {
+    pub range: (f32, f32),
+    pub windup_ms: u64,
+    pub cooldown_ms: u64,
+}
+
+impl AttackDefinition {
+    fn attack(&self) -> EnemyAttack {
+        EnemyAttack::new(
+            Vec2::new(self.range.0, self.range.1),
+            Duration::from_millis(self.windup_ms),
+            Duration::from_millis(self.cooldown_ms),
+        )
+    }
+}
+
+// projectile of the ranged enemy, gravity - scale of the world gravity, 0. for straight flight
+#[derive(Deserialize, Debug, Clone)]
+pub struct RangedDefinition {
+    pub damage: isize,
+    pub speed: f32,
+    #[serde(default)]
+    pub gravity: f32,
+    pub lifetime_ms: u64,
+    pub size: (f32, f32),
+    #[serde(default)]
+    pub interrupt: isize,
+    #[serde(default)]
+    pub knockback: f32,
+    #[serde(default)]
+    pub effects: Vec<DoTType>,
+    pub color: String,
+}
+
+impl RangedDefinition {
+    fn weapon(&self) -> RangedWeapon {
+        RangedWeapon {
+            damage: Damage(self.damage),
+            speed: self.speed,
+            gravity: self.gravity,
+            lifetime: Duration::from_millis(self.lifetime_ms),
+            size: Vec2::new(self.size.0, self.size.1),
+            interrupt: self.interrupt,
+            knockback: self.knockback,
+            effects: self
+                .effects
+                .iter()
+                .map(|t| DoTStat::from_type(*t))
+                .collect(),
+            color: Color::hex(&self.color).unwrap_or(Color::WHITE),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ContactDefinition {
+    pub damage: isize,
+    pub knockback: f32,
+    #[serde(default)]
+    pub effects: Vec<DoTType>,
+    pub cooldown_ms: u64,
+}
+
+// arena - half size of the zone locked during the fight, reward - items dropped on defeat
+#[derive(Deserialize, Debug, Clone)]
+pub struct BossDefinition {
+    pub arena: (f32, f32),
+    #[serde(default)]
+    pub reward: Vec<String>,
+    pub phases: Vec<PhaseDefinition>,
+}
+
+// health - part of max health to start the phase, speed - percent bonus to move speed
+#[derive(Deserialize, Debug, Clone)]
+pub struct PhaseDefinition {
+    pub health: f32,
+    #[serde(default)]
+    pub weapon: Option<WeaponDefinition>,
+    #[serde(default)]
+    pub attack: Option<AttackDefinition>,
+    #[serde(default)]
+    pub speed: f32,
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+impl BossDefinition {
+    fn boss(&self, max_health: i32) -> Boss {
+        Boss {
+            phases: self
+                .phases
+                .iter()
+                .map(|phase| BossPhase {
+                    health: phase.health,
+                    weapon: phase.weapon.as_ref().map(|w| w.weapon()),
+                    attack: phase.attack.as_ref().map(|a| a.attack()),
+                    speed: phase.speed,
+                    color: phase.color.as_ref().and_then(|c| Color::hex(c).ok()),
+                })
+                .collect(),
+            phase: 0,
+            max_health,
+            arena: Vec2::new(self.arena.0, self.arena.1),
+            reward: self.reward.clone(),
+            fight: false,
+            next_attack: None,
+        }
+    }
+}
+
+// chance from 0. to 1., item - name of the picture in assets/objects
+#[derive(Deserialize, Reflect, Debug, Clone)]
+pub struct LootEntry {
+    pub item: String,
+    pub chance: f32,
+}
+
+// rolled when the enemy dies
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct LootTable(pub Vec<LootEntry>);
+
+pub fn spawn_enemy(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    archetype: &EnemyArchetype,
+    position: Vec2,
+) -> Entity {
+    let stats = &archetype.stats;
+    let sprite = &archetype.sprite;
+    let mut sprite_bundle = SpriteBundle {
+        sprite: Sprite {
+            color: Color::hex(&sprite.color).unwrap_or(Color::RED),
+            custom_size: Some(Vec2::new(sprite.size.0, sprite.size.1)),
+            ..default()
+        },
+        transform: Transform::from_translation(position.extend(10.)),
+        ..default()
+    };
+    if let Some(path) = &sprite.texture {
+        sprite_bundle.texture = asset_server.load(path.as_str());
+    }
+    let mut enemy = commands.spawn(EnemyBundle {
+        name: Enemy(Name::new(archetype.name.clone())),
+        stats: Stats {
+            health: Health(stats.health),
+            armor: Armor(stats.armor),
+            strength: Strength(stats.strength),
+            speed: Speed(stats.speed),
+            ..default()
+        },
+        enemy: ActiveEntity {
+            rotation: 1,
+            current_state: EnemyStates::default(),
+        },
+        sprite: sprite_bundle,
+        rigid_body: RigidBody::KinematicVelocityBased,
+        controller: KinematicCharacterController {
+            slide: true,
+            filter_flags: QueryFilterFlags::EXCLUDE_KINEMATIC | QueryFilterFlags::EXCLUDE_SENSORS,
+            // flyer would stick to the floor under it
+            snap_to_ground: match archetype.flying {
+                Some(_) => None,
+                None => KinematicCharacterController::default().snap_to_ground,
+            },
+            ..default()
+        },
+        controller_output: KinematicCharacterControllerOutput::default(),
+        motor: archetype.motor.motor(),
+        collider: archetype.collider.collider(),
+        attack: AttackCollider(None),
+        weapon: match &archetype.weapon {
+            Some(weapon) => weapon.weapon(),
+            None => Weapon::default(),
+        },
+        impulse: Impulse::default(),
+        faction: Faction::Enemy,
+        ai: archetype.ai.ai(),
+        memory: AiMemory::default(),
+        path: NavPath::new(REPATH_TIME),
+        loot: LootTable(archetype.loot.clone()),
+    });
+    enemy.insert((
+        ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_KINEMATIC,
+        RunEntity,
+    ));
+    if let Some(attack) = &archetype.attack {
+        enemy.insert(attack.attack());
+    }
+    if let Some(ranged) = &archetype.ranged {
+        enemy.insert(ranged.weapon());
+    }
+    if let Some(contact) = &archetype.contact {
+        let mut cooldown = Timer::new(Duration::from_millis(contact.cooldown_ms), TimerMode::Once);
+        cooldown.tick(cooldown.duration()); // ready on spawn
+        enemy.insert(ContactDamage {
+            damage: Damage(contact.damage),
+            knockback: contact.knockback,
+            effects: contact
+                .effects
+                .iter()
+                .map(|t| DoTStat::from_type(*t))
+                .collect(),
+            cooldown,
+        });
+    }
+    if let Some(flying) = &archetype.flying {
+        enemy.insert(flying.ai());
+    }
+    if let Some(boss) = &archetype.boss {
+        enemy.insert(boss.boss(stats.health));
+    }
+    enemy.id()
+}
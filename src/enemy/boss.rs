@@ -0,0 +1,179 @@
+use crate::enemy::ai::{AiMemory, EnemyAttack};
+use crate::enemy::*;
+use crate::entities::*;
+use crate::events::DeathEvent;
+use crate::player::Player;
+use crate::{GameState, InGameState};
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (arena_lock, boss_phases, boss_defeated)
+                .run_if(in_state(GameState::InGame))
+                .run_if(in_state(InGameState::Play)),
+        );
+    }
+}
+
+const ARENA_WALL_WIDTH: f32 = 20.;
+
+// one phase of the fight, starts when health drops to the part of max health (0.5 - half)
+// weapon and attack replace the current ones, speed - percent bonus to move speed
+#[derive(Clone, Debug, Reflect)]
+pub struct BossPhase {
+    pub health: f32,
+    pub weapon: Option<Weapon>,
+    pub attack: Option<EnemyAttack>,
+    pub speed: f32,
+    pub color: Option<Color>,
+}
+
+// phase - count of phases already started, 0 - fight with the base archetype values
+// arena - half size of the locked zone around the post, reward - items in assets/objects
+// next_attack - attack of the phase waiting for the current attack to finish
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Boss {
+    pub phases: Vec<BossPhase>,
+    pub phase: usize,
+    pub max_health: i32,
+    pub arena: Vec2,
+    pub reward: Vec<String>,
+    pub fight: bool,
+    pub next_attack: Option<EnemyAttack>,
+}
+
+// wall that closes the arena until the boss is dead
+#[derive(Clone, Debug, Component)]
+struct ArenaWall(Entity);
+
+// the fight starts when the player enters the arena
+fn arena_lock(
+    mut commands: Commands,
+    mut boss_q: Query<(Entity, &mut Boss, &mut AiMemory), Without<Dead>>,
+    player_q: Query<&Transform, (With<Player>, Without<Dead>)>,
+) {
+    let Ok(player) = player_q.get_single() else {
+        return;
+    };
+    let player_pos = player.translation.truncate();
+    for (entity, mut boss, mut memory) in boss_q.iter_mut() {
+        if boss.fight {
+            continue;
+        }
+        let Some(post) = memory.post else {
+            continue;
+        };
+        let distance = (player_pos - post).abs();
+        if distance.x > boss.arena.x || distance.y > boss.arena.y {
+            continue;
+        }
+        boss.fight = true;
+        memory.aggro = true;
+        for side in [-1., 1.] {
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::DARK_GRAY,
+                        custom_size: Some(Vec2::new(ARENA_WALL_WIDTH * 2., boss.arena.y * 2.)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(post.x + side * boss.arena.x, post.y, 5.),
+                    ..default()
+                },
+                RigidBody::Fixed,
+                Collider::cuboid(ARENA_WALL_WIDTH, boss.arena.y),
+                ArenaWall(entity),
+                RunEntity,
+            ));
+        }
+    }
+}
+
+// speed of the phase replaces the one of the previous phase,
+// bosses are never elite so their only permanent speed modifier is the one of the phase
+fn boss_phases(
+    mut commands: Commands,
+    mut boss_q: Query<
+        (
+            Entity,
+            &mut Boss,
+            &ActiveEntity<EnemyStates>,
+            &Health,
+            &mut Weapon,
+            &mut StatModifiers,
+            &mut Sprite,
+        ),
+        Without<Dead>,
+    >,
+) {
+    for (entity, mut boss, enemy, health, mut weapon, mut modifiers, mut sprite) in
+        boss_q.iter_mut()
+    {
+        // a heavy hit can skip a phase, every skipped phase is still applied
+        while let Some(phase) = boss.phases.get(boss.phase).cloned() {
+            if health.0 as f32 > phase.health * boss.max_health as f32 {
+                break;
+            }
+            boss.phase += 1;
+            if let Some(phase_weapon) = phase.weapon {
+                *weapon = phase_weapon;
+            }
+            if phase.attack.is_some() {
+                boss.next_attack = phase.attack;
+            }
+            if phase.speed != 0. {
+                modifiers
+                    .0
+                    .retain(|m| m.time.is_some() || m.stat != StatType::MoveSpeed);
+                modifiers.0.push(ModifyStat {
+                    time: None,
+                    stat: StatType::MoveSpeed,
+                    modification: StatModificationType::Percentage,
+                    value: phase.speed,
+                    effect: phase.color.unwrap_or(sprite.color),
+                });
+            }
+            if let Some(color) = phase.color {
+                sprite.color = color;
+            }
+        }
+        // the swing in progress finishes with the old attack
+        if enemy.current_state != EnemyStates::Atack {
+            if let Some(attack) = boss.next_attack.take() {
+                commands.entity(entity).insert(attack);
+            }
+        }
+    }
+}
+
+// open the arena and drop the reward
+fn boss_defeated(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut death_events: EventReader<DeathEvent>,
+    boss_q: Query<(&Boss, &Transform)>,
+    wall_q: Query<(Entity, &ArenaWall)>,
+) {
+    for event in death_events.iter() {
+        let Ok((boss, transform)) = boss_q.get(event.entity) else {
+            continue;
+        };
+        for (wall, owner) in wall_q.iter() {
+            if owner.0 == event.entity {
+                commands.entity(wall).despawn_recursive();
+            }
+        }
+        let mut position = transform.translation;
+        for item in boss.reward.iter() {
+            spawn_pickup(&mut commands, &asset_server, item, position);
+            position.x += 30.;
+        }
+    }
+}
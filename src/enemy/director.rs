@@ -0,0 +1,220 @@
+use crate::enemy::archetype::{spawn_enemy, EnemyArchetype};
+use crate::enemy::elite::{roll_elite, EliteSettings};
+use crate::entities::Dead;
+use crate::events::RoomLoadedEvent;
+use crate::loading::EnemyAssets;
+use crate::map::nav::{NavGraph, NavNode};
+use crate::player::Player;
+use crate::{GameState, InGameState};
+
+use bevy::{prelude::*, utils::Duration};
+use rand::seq::SliceRandom;
+
+pub struct DirectorPlugin;
+
+impl Plugin for DirectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpawnDirector>()
+            .init_resource::<RunDepth>()
+            .init_resource::<RoomWaves>()
+            .add_systems(OnEnter(GameState::InGame), reset_run)
+            .add_systems(
+                Update,
+                (
+                    start_room.run_if(on_event::<RoomLoadedEvent>()),
+                    spawn_waves,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame))
+                    .run_if(in_state(InGameState::Play)),
+            );
+    }
+}
+
+// budget of the wave - base_budget + depth_budget for every room after the first + wave_budget for every previous wave
+// waves - waves in the room, the next one comes wave_delay after the previous one is cleared
+// min_distance - enemies don't appear closer to the player
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct SpawnDirector {
+    pub base_budget: u32,
+    pub depth_budget: u32,
+    pub wave_budget: u32,
+    pub waves: u32,
+    pub wave_delay: Duration,
+    pub min_distance: f32,
+}
+
+impl Default for SpawnDirector {
+    fn default() -> Self {
+        Self {
+            base_budget: 4,
+            depth_budget: 2,
+            wave_budget: 1,
+            waves: 3,
+            wave_delay: Duration::from_millis(1500),
+            min_distance: 500.,
+        }
+    }
+}
+
+impl SpawnDirector {
+    fn budget(&self, depth: u32, wave: u32) -> u32 {
+        self.base_budget + self.depth_budget * depth.saturating_sub(1) + self.wave_budget * wave
+    }
+}
+
+// rooms entered in the current run, the first room is 1
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct RunDepth(pub u32);
+
+// progress of the waves in the current room
+#[derive(Resource, Debug, Default)]
+struct RoomWaves {
+    wave: u32,
+    delay: Timer,
+}
+
+// enemy of the wave, the next wave waits until all of them are dead
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct WaveEnemy;
+
+// gap between the ground and a spawned enemy
+const SPAWN_GAP: f32 = 5.;
+
+fn reset_run(
+    director: Res<SpawnDirector>,
+    mut depth: ResMut<RunDepth>,
+    mut waves: ResMut<RoomWaves>,
+) {
+    depth.0 = 0;
+    // no waves until the room is loaded
+    waves.wave = director.waves;
+}
+
+// every loaded room is one level deeper, the first wave comes right away
+// the map has a single room for now, so the depth stays 1 until room transitions send the event
+fn start_room(
+    mut room_events: EventReader<RoomLoadedEvent>,
+    director: Res<SpawnDirector>,
+    mut depth: ResMut<RunDepth>,
+    mut waves: ResMut<RoomWaves>,
+) {
+    depth.0 += room_events.iter().count() as u32;
+    waves.wave = 0;
+    waves.delay = Timer::new(director.wave_delay, TimerMode::Once);
+    let delay = waves.delay.duration();
+    waves.delay.tick(delay);
+}
+
+// spend the budget of the wave on random archetypes the rest of the budget can pay for
+fn spawn_waves(
+    mut commands: Commands,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    enemy_assets: Res<EnemyAssets>,
+    archetypes: Res<Assets<EnemyArchetype>>,
+    director: Res<SpawnDirector>,
+    elite_settings: Res<EliteSettings>,
+    depth: Res<RunDepth>,
+    graph: Option<Res<NavGraph>>,
+    mut waves: ResMut<RoomWaves>,
+    wave_q: Query<(), (With<WaveEnemy>, Without<Dead>)>,
+    player_q: Query<&Transform, With<Player>>,
+) {
+    let Some(graph) = graph else {
+        return;
+    };
+    if waves.wave >= director.waves || !wave_q.is_empty() {
+        return;
+    }
+    waves.delay.tick(time.delta());
+    if !waves.delay.finished() {
+        return;
+    }
+    waves.delay.reset();
+
+    let player_pos = player_q.get_single().ok().map(|t| t.translation.truncate());
+    let points: Vec<&NavNode> = graph
+        .nodes
+        .iter()
+        .filter(|node| {
+            !player_pos.is_some_and(|p| node.position.distance(p) < director.min_distance)
+        })
+        .collect();
+    // archetypes of the director with the spawn points they fit in
+    let candidates: Vec<(&EnemyArchetype, Vec<&NavNode>)> = enemy_assets
+        .archetypes
+        .iter()
+        .filter_map(|handle| archetypes.get(handle))
+        .filter(|archetype| archetype.cost > 0)
+        .map(|archetype| {
+            let height = archetype.collider.half_height() * 2. + SPAWN_GAP;
+            let fits = points
+                .iter()
+                .filter(|node| node.clearance as f32 * graph.tile >= height)
+                .copied()
+                .collect::<Vec<_>>();
+            (archetype, fits)
+        })
+        .filter(|(_, fits)| !fits.is_empty())
+        .collect();
+
+    let mut budget = director.budget(depth.0, waves.wave);
+    waves.wave += 1;
+    let mut rng = rand::thread_rng();
+    loop {
+        let affordable: Vec<_> = candidates
+            .iter()
+            .filter(|(archetype, _)| archetype.cost <= budget)
+            .collect();
+        let Some((archetype, fits)) = affordable.choose(&mut rng) else {
+            break;
+        };
+        let Some(node) = fits.choose(&mut rng) else {
+            break;
+        };
+        budget -= archetype.cost;
+        // flyers appear in the middle of the free space, walkers on the ground
+        let height = match archetype.flying {
+            Some(_) => node.clearance as f32 * graph.tile / 2.,
+            None => archetype.collider.half_height() + SPAWN_GAP,
+        };
+        let enemy = spawn_enemy(
+            &mut commands,
+            &asset_server,
+            archetype,
+            node.position + Vec2::Y * height,
+        );
+        commands.entity(enemy).insert(WaveEnemy);
+        roll_elite(&mut commands, enemy, &elite_settings);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_wave_of_the_first_room_has_the_base_budget() {
+        let director = SpawnDirector::default();
+        assert_eq!(director.budget(1, 0), director.base_budget);
+        // depth is 0 before the first room is loaded
+        assert_eq!(director.budget(0, 0), director.base_budget);
+    }
+
+    #[test]
+    fn budget_grows_with_depth_and_waves() {
+        let director = SpawnDirector {
+            base_budget: 4,
+            depth_budget: 2,
+            wave_budget: 1,
+            ..default()
+        };
+        assert_eq!(director.budget(3, 0), 8);
+        assert_eq!(director.budget(1, 2), 6);
+        assert_eq!(director.budget(3, 2), 10);
+    }
+}
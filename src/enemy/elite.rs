@@ -0,0 +1,239 @@
+use crate::combat::spawn_blast;
+use crate::entities::*;
+use crate::events::DeathEvent;
+use crate::GameState;
+
+use bevy::{prelude::*, utils::Duration};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+pub struct ElitePlugin;
+
+impl Plugin for ElitePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EliteSettings>().add_systems(
+            Update,
+            (setup_elites, explode_on_death).run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+// chance - part of regular enemies spawned as elite, affixes - min and max count of rolled affixes
+// health and strength of the elite are multiplied by the scales
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct EliteSettings {
+    pub chance: f32,
+    pub health_scale: f32,
+    pub strength_scale: f32,
+    pub affixes: (usize, usize),
+}
+
+impl Default for EliteSettings {
+    fn default() -> Self {
+        Self {
+            chance: 0.2,
+            health_scale: 2.,
+            strength_scale: 1.5,
+            affixes: (1, 2),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+pub enum Affix {
+    Fast,
+    Armored,
+    Vampiric,
+    Explosive,
+    Shielded,
+}
+
+const AFFIXES: [Affix; 5] = [
+    Affix::Fast,
+    Affix::Armored,
+    Affix::Vampiric,
+    Affix::Explosive,
+    Affix::Shielded,
+];
+
+impl Affix {
+    fn color(&self) -> Color {
+        match self {
+            Affix::Fast => Color::YELLOW,
+            Affix::Armored => Color::SILVER,
+            Affix::Vampiric => Color::MAROON,
+            Affix::Explosive => Color::ORANGE,
+            Affix::Shielded => Color::CYAN,
+        }
+    }
+}
+
+// upgraded version of the enemy, stats and affixes are applied once after spawn
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Elite {
+    pub affixes: Vec<Affix>,
+}
+
+// regular enemy becomes elite with the chance of the settings, bosses are never rolled
+pub fn roll_elite(commands: &mut Commands, enemy: Entity, settings: &EliteSettings) {
+    if rand::random::<f32>() < settings.chance {
+        commands.entity(enemy).insert(Elite::roll(settings));
+    }
+}
+
+impl Elite {
+    fn roll(settings: &EliteSettings) -> Self {
+        let mut rng = rand::thread_rng();
+        let (min, max) = settings.affixes;
+        let count = rng.gen_range(min..=max.max(min));
+        Self {
+            affixes: AFFIXES.choose_multiple(&mut rng, count).copied().collect(),
+        }
+    }
+
+    // tint of the sprite, mix of the affix colors
+    fn tint(&self) -> Option<Color> {
+        if self.affixes.is_empty() {
+            return None;
+        }
+        let sum = self.affixes.iter().fold(Vec4::ZERO, |sum, affix| {
+            sum + Vec4::from(affix.color().as_rgba_f32())
+        });
+        Some(Color::from(sum / self.affixes.len() as f32))
+    }
+}
+
+const FAST_SPEED: f32 = 50.;
+const ARMORED_ARMOR: i32 = 5;
+const VAMPIRIC_PART: f32 = 0.5;
+// part of the max health
+const SHIELD_PART: f32 = 0.5;
+
+fn setup_elites(
+    mut commands: Commands,
+    settings: Res<EliteSettings>,
+    mut elite_q: Query<
+        (
+            Entity,
+            &Elite,
+            &mut Health,
+            &mut Strength,
+            &mut Armor,
+            &mut StatModifiers,
+            &mut Sprite,
+        ),
+        Added<Elite>,
+    >,
+) {
+    for (entity, elite, mut health, mut strength, mut armor, mut modifiers, mut sprite) in
+        elite_q.iter_mut()
+    {
+        health.0 = (health.0 as f32 * settings.health_scale).round() as i32;
+        strength.0 = (strength.0 as f32 * settings.strength_scale).round() as i32;
+        if let Some(tint) = elite.tint() {
+            sprite.color = tint;
+        }
+        for affix in elite.affixes.iter() {
+            match affix {
+                Affix::Fast => modifiers.0.push(ModifyStat {
+                    time: None,
+                    stat: StatType::MoveSpeed,
+                    modification: StatModificationType::Percentage,
+                    value: FAST_SPEED,
+                    effect: affix.color(),
+                }),
+                Affix::Armored => armor.0 += ARMORED_ARMOR,
+                Affix::Vampiric => {
+                    commands.entity(entity).insert(LifeSteal {
+                        part: VAMPIRIC_PART,
+                        max_health: health.0,
+                    });
+                }
+                Affix::Shielded => {
+                    let shield = (health.0 as f32 * SHIELD_PART).round() as i32;
+                    commands.entity(entity).insert(Shield(shield));
+                }
+                // handled on death
+                Affix::Explosive => (),
+            }
+        }
+    }
+}
+
+fn explosion() -> Weapon {
+    Weapon {
+        name: "Explosion".into(),
+        damage: Damage(3),
+        attack_time: Duration::from_millis(150),
+        hitbox: Vec2::new(120., 120.),
+        offset: Vec2::ZERO,
+        interrupt: 300,
+        knockback: 1000.,
+        effects: vec![DoTStat::burn()],
+        combo: Vec::new(),
+        combo_window: Duration::ZERO,
+    }
+}
+
+// the corpse explodes, the blast goes through the attack pipeline with the elite as the attacker
+// and stays on the place of death when the corpse is removed
+fn explode_on_death(
+    mut commands: Commands,
+    mut death_events: EventReader<DeathEvent>,
+    elite_q: Query<(&Elite, &Faction, &Transform)>,
+) {
+    for event in death_events.iter() {
+        let Ok((elite, faction, transform)) = elite_q.get(event.entity) else {
+            continue;
+        };
+        if elite.affixes.contains(&Affix::Explosive) {
+            spawn_blast(
+                &mut commands,
+                event.entity,
+                *faction,
+                &explosion(),
+                transform.translation.truncate(),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolled_affixes_are_unique_and_in_range() {
+        let settings = EliteSettings {
+            affixes: (1, 3),
+            ..default()
+        };
+        for _ in 0..100 {
+            let elite = Elite::roll(&settings);
+            assert!((1..=3).contains(&elite.affixes.len()));
+            for (index, affix) in elite.affixes.iter().enumerate() {
+                assert!(!elite.affixes[index + 1..].contains(affix));
+            }
+        }
+    }
+
+    #[test]
+    fn max_below_min_rolls_min() {
+        let settings = EliteSettings {
+            affixes: (2, 0),
+            ..default()
+        };
+        assert_eq!(Elite::roll(&settings).affixes.len(), 2);
+    }
+
+    #[test]
+    fn no_tint_without_affixes() {
+        assert!(Elite::default().tint().is_none());
+        let elite = Elite {
+            affixes: vec![Affix::Armored],
+        };
+        assert_eq!(elite.tint(), Some(Affix::Armored.color()));
+    }
+}
@@ -0,0 +1,144 @@
+use crate::enemy::ai::{enemy_senses, AiMemory, POST_REACHED};
+use crate::enemy::group::{Attackers, GroupSettings};
+use crate::enemy::*;
+use crate::entities::*;
+use crate::motor::{CharacterMotor, MotorSet};
+use crate::player::Player;
+use crate::{GameState, InGameState};
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+pub struct FlyingPlugin;
+
+impl Plugin for FlyingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            flying_ai
+                .in_set(MotorSet::Input)
+                .after(enemy_senses)
+                .run_if(in_state(GameState::InGame))
+                .run_if(in_state(InGameState::Play)),
+        );
+    }
+}
+
+// distance to the target where the flyer starts to slow down
+const ARRIVE_DISTANCE: f32 = 60.;
+
+// movement of the flying enemy, the CharacterMotor of it has gravity_scale 0.
+// Speed of the flyer is its cruise velocity
+// hover_height - height above the player while chasing, steering - part of the velocity turned
+// to the desired one per frame (1. - instant turn), avoid_distance - length of the terrain ray
+// swoop_range - distance to the player to start the dive, swoop - duration of the dive
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct FlyingAi {
+    pub hover_height: f32,
+    pub steering: f32,
+    pub avoid_distance: f32,
+    pub swoop_range: f32,
+    pub swoop_speed: f32,
+    pub swoop: Timer,
+    pub cooldown: Timer,
+    pub swoop_target: Vec2,
+}
+
+// Idle/Return -> Run (hover above the player) -> Atack (dive to the point where the player was) -> Run
+fn flying_ai(
+    time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    group: Res<GroupSettings>,
+    mut attackers: ResMut<Attackers>,
+    mut enemy_q: Query<
+        (
+            &mut FlyingAi,
+            &AiMemory,
+            &mut ActiveEntity<EnemyStates>,
+            &mut CharacterMotor,
+            &Transform,
+            &Speed,
+            &StatModifiers,
+            Option<&Stunned>,
+        ),
+        (With<Enemy>, Without<Dead>),
+    >,
+    player_q: Query<&Transform, (With<Player>, Without<Dead>)>,
+) {
+    let player_pos = player_q.get_single().ok().map(|t| t.translation.truncate());
+    for (mut fly, memory, mut enemy, mut motor, enemy_pos, speed, modifiers, stun) in
+        enemy_q.iter_mut()
+    {
+        let position = enemy_pos.translation.truncate();
+        let post = memory.post.unwrap_or(position);
+        let speed = modifiers.apply(StatType::MoveSpeed, speed.0);
+        fly.cooldown.tick(time.delta());
+
+        if stun.is_some() {
+            // hit breaks the dive
+            if enemy.current_state == EnemyStates::Atack {
+                enemy.current_state = EnemyStates::Run;
+                fly.cooldown.reset();
+            }
+            continue;
+        }
+
+        if enemy.current_state == EnemyStates::Atack {
+            fly.swoop.tick(time.delta());
+            if fly.swoop.finished() {
+                enemy.current_state = EnemyStates::Run;
+                fly.cooldown.reset();
+            }
+        }
+
+        let (target, max_speed) = match (memory.aggro, player_pos) {
+            _ if enemy.current_state == EnemyStates::Atack => (fly.swoop_target, fly.swoop_speed),
+            (true, Some(player_pos)) => {
+                if fly.cooldown.finished()
+                    && position.distance(player_pos) <= fly.swoop_range
+                    && attackers.try_join(&group)
+                {
+                    enemy.current_state = EnemyStates::Atack;
+                    fly.swoop.reset();
+                    fly.swoop_target = player_pos;
+                    (player_pos, fly.swoop_speed)
+                } else {
+                    enemy.current_state = EnemyStates::Run;
+                    (player_pos + Vec2::Y * fly.hover_height, speed)
+                }
+            }
+            _ => {
+                enemy.current_state = if position.distance(post) > POST_REACHED {
+                    EnemyStates::Return
+                } else {
+                    EnemyStates::Idle
+                };
+                (post, speed)
+            }
+        };
+
+        let to_target = target - position;
+        let slowdown = (to_target.length() / ARRIVE_DISTANCE).min(1.);
+        let mut desired = to_target.normalize_or_zero() * max_speed * slowdown;
+
+        // turn away from the wall in front
+        if let Some(direction) = desired.try_normalize() {
+            if let Some((_, hit)) = rapier_context.cast_ray_and_get_normal(
+                position,
+                direction,
+                fly.avoid_distance,
+                true,
+                QueryFilter::only_fixed(),
+            ) {
+                desired += hit.normal * max_speed;
+            }
+        }
+
+        if desired.x != 0. {
+            enemy.rotation = if desired.x < 0. { -1 } else { 1 };
+        }
+        let current = motor.velocity * motor.air_friction;
+        motor.input += (desired - current) * fly.steering;
+    }
+}
@@ -0,0 +1,124 @@
+use crate::enemy::ai::enemy_senses;
+use crate::enemy::*;
+use crate::entities::*;
+use crate::motor::{CharacterMotor, MotorSet};
+use crate::{GameState, InGameState};
+
+use bevy::prelude::*;
+
+pub struct GroupPlugin;
+
+impl Plugin for GroupPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GroupSettings>()
+            .init_resource::<Attackers>()
+            .add_systems(
+                Update,
+                (
+                    count_attackers.before(enemy_senses),
+                    enemy_separation.in_set(MotorSet::Input),
+                )
+                    .run_if(in_state(GameState::InGame))
+                    .run_if(in_state(InGameState::Play)),
+            );
+    }
+}
+
+// separation_radius - enemies closer than it push each other apart with separation_force
+// max_attackers - enemies attacking the player at the same time, the rest wait their turn
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct GroupSettings {
+    pub separation_radius: f32,
+    pub separation_force: f32,
+    pub max_attackers: usize,
+}
+
+impl Default for GroupSettings {
+    fn default() -> Self {
+        Self {
+            separation_radius: 80.,
+            separation_force: 25.,
+            max_attackers: 2,
+        }
+    }
+}
+
+// enemies in the Atack state, counted every frame and taken by enemies starting an attack
+#[derive(Resource, Debug, Default)]
+pub struct Attackers(usize);
+
+impl Attackers {
+    // take a place in the group attack, false when all places are taken
+    pub fn try_join(&mut self, settings: &GroupSettings) -> bool {
+        if self.0 >= settings.max_attackers {
+            return false;
+        }
+        self.0 += 1;
+        true
+    }
+}
+
+fn count_attackers(
+    mut attackers: ResMut<Attackers>,
+    enemy_q: Query<&ActiveEntity<EnemyStates>, (With<Enemy>, Without<Dead>)>,
+) {
+    attackers.0 = enemy_q
+        .iter()
+        .filter(|enemy| enemy.current_state == EnemyStates::Atack)
+        .count();
+}
+
+// walking enemies are pushed only horizontally, flying ones in any direction
+fn enemy_separation(
+    settings: Res<GroupSettings>,
+    mut enemy_q: Query<(Entity, &Transform, &mut CharacterMotor), (With<Enemy>, Without<Dead>)>,
+) {
+    let positions: Vec<(Entity, Vec2)> = enemy_q
+        .iter()
+        .map(|(entity, transform, _)| (entity, transform.translation.truncate()))
+        .collect();
+    for (entity, transform, mut motor) in enemy_q.iter_mut() {
+        let position = transform.translation.truncate();
+        let mut push = Vec2::ZERO;
+        for (other, other_position) in positions.iter() {
+            if *other == entity {
+                continue;
+            }
+            let offset = position - *other_position;
+            let distance = offset.length();
+            if distance >= settings.separation_radius {
+                continue;
+            }
+            // spawned at the same point, split them by the order of entities
+            let away = match offset.try_normalize() {
+                Some(away) => away,
+                None if entity.index() < other.index() => Vec2::NEG_X,
+                None => Vec2::X,
+            };
+            push += away * (1. - distance / settings.separation_radius);
+        }
+        if motor.gravity_scale != 0. {
+            push.y = 0.;
+        }
+        motor.input += push * settings.separation_force;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn places_in_the_attack_are_limited() {
+        let settings = GroupSettings {
+            max_attackers: 2,
+            ..default()
+        };
+        let mut attackers = Attackers::default();
+        assert!(attackers.try_join(&settings));
+        assert!(attackers.try_join(&settings));
+        assert!(!attackers.try_join(&settings));
+        assert_eq!(attackers.0, 2);
+    }
+}
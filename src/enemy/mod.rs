@@ -1,17 +1,38 @@
-mod goblin;
-mod slime;
+mod ai;
+pub mod archetype;
+mod boss;
+mod director;
+mod elite;
+mod flying;
+mod group;
 
-// TODO: Think about it:
 /**
  *
- * A general function for movement, where the opponent will have a distance and attack offset based on which he stops at a certain attack distance.
- * Calls the attack method.
- * Also, if there are any movement specifications,
- * they can be added in a separate subsystem or values can be assigned to the fields of the enemy structure
+ * A general function for movement (ai.rs), where the opponent will have a distance and attack offset based on which he stops at a certain attack distance.
+ * Enemies with EnemyAttack call the attack method in range.
+ * Enemies keep apart and take turns attacking (group.rs)
+ * Flying enemies replace the ground movement with flying.rs, senses are shared
+ * Bosses are archetypes with a boss section, phases and arena are handled in boss.rs
+ * Any other enemy may spawn elite with scaled stats and random affixes (elite.rs)
+ * Regular enemies come in waves bought with the difficulty budget of the room (director.rs)
+ * Movement specifications are values of the EnemyAi fields,
+ * a new enemy type is a new .enemy.ron file in assets/enemies (archetype.rs)
  */
-use crate::enemy::goblin::GoblinsPlugin;
-use crate::enemy::slime::SlimesPlugin;
+use crate::combat::DeathSettings;
+use crate::enemy::ai::{AiMemory, EnemyAi, EnemyAiPlugin, EnemyAttack};
+use crate::enemy::archetype::{spawn_enemy, EnemyArchetype, LootTable};
+use crate::enemy::boss::BossPlugin;
+use crate::enemy::director::DirectorPlugin;
+use crate::enemy::elite::ElitePlugin;
+use crate::enemy::flying::FlyingPlugin;
+use crate::enemy::group::GroupPlugin;
 use crate::entities::*;
+use crate::events::{AttackEvent, DeathEvent};
+use crate::loading::EnemyAssets;
+use crate::map::nav::NavPath;
+use crate::motor::CharacterMotor;
+use crate::player::{class::ClassItem, Player};
+use crate::{GameState, InGameState};
 
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
@@ -20,8 +41,22 @@ pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((SlimesPlugin, GoblinsPlugin));
-        // .add_systems(Update, sensor_event.run_if(in_state(GameState::InGame)));
+        app.add_plugins((
+            EnemyAiPlugin,
+            FlyingPlugin,
+            GroupPlugin,
+            BossPlugin,
+            ElitePlugin,
+            DirectorPlugin,
+        ))
+        .add_systems(OnEnter(GameState::InGame), spawn_enemies)
+        .add_systems(Update, enemy_death.run_if(in_state(GameState::InGame)))
+        .add_systems(
+            Update,
+            (contact_damage, collect_loot)
+                .run_if(in_state(GameState::InGame))
+                .run_if(in_state(InGameState::Play)),
+        );
     }
 }
 
@@ -31,22 +66,177 @@ enum EnemyStates {
     Idle,
     Run,
     Atack,
+    Patrol,
+    Return,
+    Death,
 }
 
 #[derive(Clone, Default, Debug, Component, Reflect)]
 #[reflect(Component)]
 struct Enemy(Name);
 
+// damage to the player on touch
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+struct ContactDamage {
+    damage: Damage,
+    knockback: f32,
+    effects: Vec<DoTStat>,
+    cooldown: Timer,
+}
+
 #[derive(Bundle, Default)]
-struct EnemyBundle<T: Default + Component> {
+struct EnemyBundle {
     name: Enemy,
-    monster_type: T,
     stats: Stats,
     enemy: ActiveEntity<EnemyStates>,
     sprite: SpriteBundle,
     rigid_body: RigidBody,
     controller: KinematicCharacterController,
     controller_output: KinematicCharacterControllerOutput,
+    motor: CharacterMotor,
     collider: Collider,
     attack: AttackCollider,
+    weapon: Weapon,
+    impulse: Impulse,
+    faction: Faction,
+    ai: EnemyAi,
+    memory: AiMemory,
+    path: NavPath,
+    loot: LootTable,
+}
+
+fn spawn_enemies(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    enemy_assets: Res<EnemyAssets>,
+    archetypes: Res<Assets<EnemyArchetype>>,
+) {
+    for handle in enemy_assets.archetypes.iter() {
+        let Some(archetype) = archetypes.get(handle) else {
+            continue;
+        };
+        for (x, y) in archetype.spawns.iter() {
+            spawn_enemy(&mut commands, &asset_server, archetype, Vec2::new(*x, *y));
+        }
+    }
+}
+
+fn enemy_death(
+    mut commands: Commands,
+    settings: Res<DeathSettings>,
+    asset_server: Res<AssetServer>,
+    mut death_events: EventReader<DeathEvent>,
+    mut enemy_q: Query<
+        (
+            &mut ActiveEntity<EnemyStates>,
+            &mut Sprite,
+            &Transform,
+            Option<&LootTable>,
+        ),
+        With<Enemy>,
+    >,
+) {
+    for event in death_events.iter() {
+        let Ok((mut enemy, mut sprite, transform, loot)) = enemy_q.get_mut(event.entity) else {
+            continue;
+        };
+        enemy.current_state = EnemyStates::Death;
+        sprite.color = Color::GRAY;
+        commands
+            .entity(event.entity)
+            .insert(Corpse(Timer::new(settings.corpse_time, TimerMode::Once)));
+
+        let Some(loot) = loot else {
+            continue;
+        };
+        let mut position = transform.translation;
+        for entry in loot.0.iter() {
+            if rand::random::<f32>() >= entry.chance {
+                continue;
+            }
+            spawn_pickup(&mut commands, &asset_server, &entry.item, position);
+            position.x += 30.;
+        }
+    }
+}
+
+// half size of the pickup collider before the scale of the sprite
+const PICKUP_SIZE: f32 = 8.;
+
+// sensor without a body, picked up on the collision event with the player
+fn spawn_pickup(commands: &mut Commands, asset_server: &AssetServer, item: &str, position: Vec3) {
+    commands.spawn((
+        SpriteBundle {
+            texture: asset_server.load(format!("objects/{}.png", item)),
+            transform: Transform::from_translation(position).with_scale(Vec3::splat(3.)),
+            ..default()
+        },
+        Collider::cuboid(PICKUP_SIZE, PICKUP_SIZE),
+        Sensor,
+        ActiveEvents::COLLISION_EVENTS,
+        ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_STATIC,
+        Pickup(item.to_string()),
+        RunEntity,
+    ));
+}
+
+// loot goes to the inventory of the player on touch, class runes are handled in class.rs
+fn collect_loot(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    pickup_q: Query<&Pickup, Without<ClassItem>>,
+    mut player_q: Query<(Entity, &mut Inventory), (With<Player>, Without<Dead>)>,
+) {
+    let Ok((player, mut inventory)) = player_q.get_single_mut() else {
+        return;
+    };
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(first, second, _) = collision_event else {
+            continue;
+        };
+        let item = match (*first, *second) {
+            (entity, item) | (item, entity) if entity == player => item,
+            _ => continue,
+        };
+        let Ok(pickup) = pickup_q.get(item) else {
+            continue;
+        };
+        *inventory.0.entry(pickup.0.clone()).or_default() += 1;
+        commands.entity(item).despawn_recursive();
+    }
+}
+
+fn contact_damage(
+    time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    mut enemy_q: Query<(Entity, &mut ContactDamage), (With<Enemy>, Without<Dead>)>,
+    player_q: Query<Entity, (With<Player>, Without<Dead>)>,
+    mut attack_events: EventWriter<AttackEvent>,
+) {
+    let Ok(player) = player_q.get_single() else {
+        return;
+    };
+    for (entity, mut contact) in enemy_q.iter_mut() {
+        contact.cooldown.tick(time.delta());
+        if !contact.cooldown.finished() {
+            continue;
+        }
+        let touch = match rapier_context.contact_pair(entity, player) {
+            Some(pair) => pair.has_any_active_contacts(),
+            None => false,
+        };
+        if !touch {
+            continue;
+        }
+        contact.cooldown.reset();
+        attack_events.send(AttackEvent {
+            attacker: entity,
+            target: player,
+            damage: contact.damage.0,
+            effects: contact.effects.clone(),
+            interrupt: 0,
+            knockback: contact.knockback,
+        });
+    }
 }
@@ -1,7 +1,7 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::Duration, utils::HashMap};
+use serde::Deserialize;
 
 // **********************************************************  STATS
-// add speed how stats
 // stats for weapon > attack damage, attack speed, attack range, attack interrupt, attack knockback
 // Strength * attack damage = true damage
 #[derive(Clone, Debug, Component, Reflect)]
@@ -22,6 +22,11 @@ pub struct Armor(pub i32);
 #[reflect(Component)]
 pub struct Strength(pub i32);
 
+// base move speed, effective speed calculated with StatModifiers
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Speed(pub f32);
+
 // *********************************************************  END STATS
 
 #[derive(Bundle, Default)]
@@ -29,17 +34,275 @@ pub struct Stats {
     pub health: Health,
     pub armor: Armor,
     pub strength: Strength,
+    pub speed: Speed,
+    pub modifiers: StatModifiers,
 }
 
 #[derive(Clone, Default, Debug, Component, Reflect)]
 #[reflect(Component)]
 pub struct AttackCollider(pub Option<Entity>);
 
+// side of the entity, attacks don't hit the same faction
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Component, Reflect)]
+#[reflect(Component)]
+pub enum Faction {
+    #[default]
+    Player,
+    Enemy,
+}
+
+// marker of entity with zero health, it no longer moves or takes damage
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Dead;
+
+// dead entity despawned after timer
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Corpse(pub Timer);
+
+// sensor of a single swing, child of the attacking entity
+// targets are remembered so each one is hit only once per swing
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct HitBox {
+    pub damage: Damage,
+    pub effects: Vec<DoTStat>,
+    pub hit: Vec<Entity>,
+    pub lifetime: Timer,
+    pub interrupt: isize,
+    pub knockback: f32,
+    pub direction: AttackDirection,
+}
+
+// sensor of an attack without a body (explosion), root entity at the position of the blast
+// owner gets the hits, faction decides who is hit, targets are hit once
+#[derive(Clone, Debug, Component)]
+pub struct Blast {
+    pub owner: Entity,
+    pub faction: Faction,
+    pub damage: Damage,
+    pub effects: Vec<DoTStat>,
+    pub hit: Vec<Entity>,
+    pub lifetime: Timer,
+    pub interrupt: isize,
+    pub knockback: f32,
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Reflect)]
+pub enum AttackDirection {
+    #[default]
+    Side,
+    Up,
+    Down,
+}
+
+// item lying on the map, name of the picture in assets/objects
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Pickup(pub String);
+
+// count of the picked up items by the name, coins and keys of the loot
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Inventory(pub HashMap<String, u32>);
+
+// spikes on the map, down attack bounces off it
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Hazard;
+
+// sensor of a hazard, hurts everyone inside once per cooldown
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct HazardDamage {
+    pub damage: i32,
+    pub knockback: f32,
+    pub cooldown: Timer,
+}
+
+// entity of the current run, despawned on game over
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct RunEntity;
+
+// equipped weapon, drives hitbox shape, timing and damage of the attack
+// hitbox - half size of the sensor, offset - position of the sensor when looking right
+// interrupt - stun of the target in milliseconds
+// combo - chain of attacks, empty for a single swing
+// combo_window - time after a swing to press attack again and continue the chain
+#[derive(Clone, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Weapon {
+    pub name: String,
+    pub damage: Damage,
+    pub attack_time: Duration,
+    pub hitbox: Vec2,
+    pub offset: Vec2,
+    pub interrupt: isize,
+    pub knockback: f32,
+    pub effects: Vec<DoTStat>,
+    pub combo: Vec<ComboStep>,
+    pub combo_window: Duration,
+}
+
+// one attack of the combo chain
+#[derive(Clone, Debug, Reflect)]
+pub struct ComboStep {
+    pub damage_multiplier: f32,
+    pub attack_time: Duration,
+    pub hitbox: Vec2,
+    pub offset: Vec2,
+}
+
+impl ComboStep {
+    pub fn new(damage_multiplier: f32, attack_ms: u64, hitbox: Vec2, offset: Vec2) -> Self {
+        Self {
+            damage_multiplier,
+            attack_time: Duration::from_millis(attack_ms),
+            hitbox,
+            offset,
+        }
+    }
+}
+
+impl Weapon {
+    // weapon with the hitbox, time and damage of the combo step
+    pub fn combo_step(&self, step: usize) -> Self {
+        let mut weapon = self.clone();
+        if let Some(combo) = self.combo.get(step) {
+            weapon.damage =
+                Damage((self.damage.0 as f32 * combo.damage_multiplier).round() as isize);
+            weapon.attack_time = combo.attack_time;
+            weapon.hitbox = combo.hitbox;
+            weapon.offset = combo.offset;
+        }
+        weapon
+    }
+}
+
+// weapon that fires projectiles, the hit goes through the attack pipeline like a melee hit
+// size - half size of the projectile collider, gravity - scale of the world gravity, 0. for straight flight
+#[derive(Clone, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct RangedWeapon {
+    pub damage: Damage,
+    pub speed: f32,
+    pub gravity: f32,
+    pub lifetime: Duration,
+    pub size: Vec2,
+    pub interrupt: isize,
+    pub knockback: f32,
+    pub effects: Vec<DoTStat>,
+    pub color: Color,
+}
+
+impl Default for RangedWeapon {
+    fn default() -> Self {
+        Self {
+            damage: Damage(1),
+            speed: 600.,
+            gravity: 0.,
+            lifetime: Duration::from_secs(2),
+            size: Vec2::new(12., 3.),
+            interrupt: 0,
+            knockback: 200.,
+            effects: Vec::new(),
+            color: Color::WHITE,
+        }
+    }
+}
+
+// flying projectile, despawned on hit, on terrain or when lifetime is over
+#[derive(Clone, Debug, Component)]
+pub struct Projectile {
+    pub owner: Entity,
+    pub faction: Faction,
+    pub damage: Damage,
+    pub effects: Vec<DoTStat>,
+    pub interrupt: isize,
+    pub knockback: f32,
+    pub velocity: Vec2,
+    pub gravity: f32,
+    pub lifetime: Timer,
+}
+
+// progress of the combo chain, step - index of the next attack
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Combo {
+    pub step: usize,
+    pub window: Timer,
+    pub queued: bool,
+}
+
+// bare hands, weapons themselves are loaded from assets/weapons
+impl Default for Weapon {
+    fn default() -> Self {
+        Self {
+            name: "Fists".into(),
+            damage: Damage(1),
+            attack_time: Duration::from_millis(300),
+            hitbox: Vec2::new(5., 8.),
+            offset: Vec2::new(12., 0.),
+            interrupt: 100,
+            knockback: 200.,
+            effects: Vec::new(),
+            combo: Vec::new(),
+            combo_window: Duration::ZERO,
+        }
+    }
+}
+
+// external velocity (knockback, explosions), added by the CharacterMotor and cleared
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Impulse(pub Vec2);
+
+// entity doesn't take damage until timer finished, blink - flicker the sprite
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Invulnerable {
+    pub timer: Timer,
+    pub blink: bool,
+}
+
+impl Invulnerable {
+    pub fn new(time: Duration, blink: bool) -> Self {
+        Self {
+            timer: Timer::new(time, TimerMode::Once),
+            blink,
+        }
+    }
+}
+
+// absorbs damage before health, broken shield stays with 0
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Shield(pub i32);
+
+// part of the dealt damage of direct hits returned as health, up to max_health
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct LifeSteal {
+    pub part: f32,
+    pub max_health: i32,
+}
+
+// invulnerability window after a direct hit
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct HitInvulnerability(pub Duration);
+
+// entity can't move or attack until timer finished
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Stunned(pub Timer);
+
 #[derive(Clone, Default, Debug, Component, Reflect)]
 #[reflect(Component)]
 pub struct ActiveEntity<T: Default> {
     pub rotation: i8,
-    pub velocity: Vec2,
     pub current_state: T,
 }
 
@@ -47,33 +310,240 @@ pub struct ActiveEntity<T: Default> {
 #[reflect(Component)]
 pub struct Damage(pub isize);
 
-#[derive(Clone, Default, Debug, Component, Reflect)]
-#[reflect(Component)]
+// time None - permanent modifier (artifacts)
+#[derive(Clone, Default, Debug, Reflect)]
 pub struct ModifyStat {
-    pub time: Timer,
-    pub speed_scale: isize,
+    pub time: Option<Timer>,
+    pub stat: StatType,
+    pub modification: StatModificationType,
+    pub value: f32,
     pub effect: Color,
 }
 
+impl ModifyStat {
+    pub fn is_expired(&self) -> bool {
+        match &self.time {
+            Some(time) => time.finished(),
+            None => false,
+        }
+    }
+}
+
+// active modifiers of entity, ticked in combat.rs
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatModifiers(pub Vec<ModifyStat>);
+
+impl StatModifiers {
+    // order: absolute replaces base value (last one wins), numerical are added, percentage scales the sum
+    pub fn apply(&self, stat: StatType, base: f32) -> f32 {
+        let mut value = base;
+        let mut numerical = 0.;
+        let mut percentage = 0.;
+        for modifier in self.0.iter().filter(|m| m.stat == stat) {
+            match modifier.modification {
+                StatModificationType::Absolute => value = modifier.value,
+                StatModificationType::Numerical => numerical += modifier.value,
+                StatModificationType::Percentage => percentage += modifier.value,
+                StatModificationType::None => (),
+            }
+        }
+        ((value + numerical) * (1. + percentage / 100.)).max(0.)
+    }
+
+    // timed modifiers run out, the expired ones are removed
+    pub fn tick(&mut self, delta: Duration) {
+        for modifier in self.0.iter_mut() {
+            if let Some(timer) = modifier.time.as_mut() {
+                timer.tick(delta);
+            }
+        }
+        self.0.retain(|modifier| !modifier.is_expired());
+    }
+}
+
+// damage over time, lives as a child entity of the target
+// stuck - stun in milliseconds on apply, 0 without stun
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
 pub struct DoTStat {
+    pub effect_type: DoTType,
+    pub stacking: DoTStacking,
     pub time: Timer,
     pub tick_dot: Timer,
     pub damage: Damage,
     pub stuck: isize,
-    // pub effect_type
+    pub stacks: u8,
+    pub source: Option<Entity>,
+}
+
+impl DoTStat {
+    pub fn new(
+        effect_type: DoTType,
+        stacking: DoTStacking,
+        damage: isize,
+        tick_ms: u64,
+        time_ms: u64,
+    ) -> Self {
+        Self {
+            effect_type,
+            stacking,
+            time: Timer::new(Duration::from_millis(time_ms), TimerMode::Once),
+            tick_dot: Timer::new(Duration::from_millis(tick_ms), TimerMode::Repeating),
+            damage: Damage(damage),
+            stuck: 0,
+            stacks: 1,
+            source: None,
+        }
+    }
+
+    pub fn poison() -> Self {
+        Self::new(DoTType::Poison, DoTStacking::Intensity, 2, 500, 4000)
+    }
+
+    pub fn burn() -> Self {
+        Self::new(DoTType::Burn, DoTStacking::Refresh, 4, 250, 2000)
+    }
+
+    pub fn bleed() -> Self {
+        Self::new(DoTType::Bleed, DoTStacking::Ignore, 3, 300, 3000)
+    }
+
+    pub fn from_type(effect_type: DoTType) -> Self {
+        match effect_type {
+            DoTType::Poison => Self::poison(),
+            DoTType::Burn => Self::burn(),
+            DoTType::Bleed => Self::bleed(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash, Reflect, Deserialize)]
+pub enum DoTType {
+    #[default]
+    Poison,
+    Burn,
+    Bleed,
+}
+
+// what happens when the same type of DoT hits the target again
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Reflect)]
+pub enum DoTStacking {
+    // restart the duration
+    #[default]
+    Refresh,
+    // add one more stack and restart the duration
+    Intensity,
+    // keep the current effect
+    Ignore,
 }
 
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Reflect)]
 pub enum StatType {
+    #[default]
     AttackDamage,
     MoveSpeed,
-    
 }
 
-
+// value of Percentage in percents: 50. is +50%, -30. is -30%
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Reflect)]
 pub enum StatModificationType {
     Percentage,
     Numerical,
     Absolute,
+    #[default]
     None,
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn modifier(stat: StatType, modification: StatModificationType, value: f32) -> ModifyStat {
+        ModifyStat {
+            time: None,
+            stat,
+            modification,
+            value,
+            effect: Color::WHITE,
+        }
+    }
+
+    #[test]
+    fn modifiers_of_other_stats_are_ignored() {
+        let modifiers = StatModifiers(vec![modifier(
+            StatType::AttackDamage,
+            StatModificationType::Numerical,
+            5.,
+        )]);
+        assert_eq!(modifiers.apply(StatType::MoveSpeed, 50.), 50.);
+    }
+
+    #[test]
+    fn percentage_scales_the_sum() {
+        let modifiers = StatModifiers(vec![
+            modifier(StatType::MoveSpeed, StatModificationType::Numerical, 10.),
+            modifier(StatType::MoveSpeed, StatModificationType::Percentage, 50.),
+            modifier(StatType::MoveSpeed, StatModificationType::Percentage, 50.),
+        ]);
+        assert_eq!(modifiers.apply(StatType::MoveSpeed, 40.), 100.);
+    }
+
+    #[test]
+    fn absolute_replaces_the_base() {
+        let modifiers = StatModifiers(vec![
+            modifier(StatType::MoveSpeed, StatModificationType::Absolute, 10.),
+            modifier(StatType::MoveSpeed, StatModificationType::Absolute, 20.),
+            modifier(StatType::MoveSpeed, StatModificationType::Numerical, 5.),
+        ]);
+        assert_eq!(modifiers.apply(StatType::MoveSpeed, 40.), 25.);
+    }
+
+    #[test]
+    fn stat_never_goes_below_zero() {
+        let modifiers = StatModifiers(vec![modifier(
+            StatType::MoveSpeed,
+            StatModificationType::Percentage,
+            -150.,
+        )]);
+        assert_eq!(modifiers.apply(StatType::MoveSpeed, 40.), 0.);
+    }
+
+    #[test]
+    fn timed_modifier_expires() {
+        let mut timed = modifier(StatType::MoveSpeed, StatModificationType::Numerical, 10.);
+        timed.time = Some(Timer::new(Duration::from_secs(2), TimerMode::Once));
+        let mut modifiers = StatModifiers(vec![
+            timed,
+            modifier(StatType::AttackDamage, StatModificationType::Numerical, 5.),
+        ]);
+
+        modifiers.tick(Duration::from_secs(1));
+        assert_eq!(modifiers.apply(StatType::MoveSpeed, 40.), 50.);
+
+        modifiers.tick(Duration::from_secs(1));
+        assert_eq!(modifiers.0.len(), 1);
+        assert!(modifiers.0.iter().all(|m| !m.is_expired()));
+        assert_eq!(modifiers.apply(StatType::MoveSpeed, 40.), 40.);
+        // permanent modifiers stay
+        assert_eq!(modifiers.apply(StatType::AttackDamage, 3.), 8.);
+    }
+
+    #[test]
+    fn combo_step_scales_the_damage() {
+        let weapon = Weapon {
+            damage: Damage(3),
+            combo: vec![
+                ComboStep::new(1., 200, Vec2::new(4., 6.), Vec2::new(11., 0.)),
+                ComboStep::new(2., 350, Vec2::new(8., 6.), Vec2::new(13., 0.)),
+            ],
+            ..default()
+        };
+        let last = weapon.combo_step(1);
+        assert_eq!(last.damage.0, 6);
+        assert_eq!(last.attack_time, Duration::from_millis(350));
+        assert_eq!(last.hitbox, Vec2::new(8., 6.));
+        // step out of the chain is the plain weapon
+        assert_eq!(weapon.combo_step(5).damage.0, 3);
+    }
+}
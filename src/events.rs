@@ -1,8 +1,50 @@
+use crate::entities::DoTStat;
 use bevy::prelude::*;
 
+// attacker hit target with a weapon damage, final damage calculated in combat.rs
 #[derive(Event, Debug)]
 pub struct AttackEvent {
-    pub entity: Entity,
+    pub attacker: Entity,
+    pub target: Entity,
     pub damage: isize,
-    pub effects: Vec<isize>,
+    pub effects: Vec<DoTStat>,
+    pub interrupt: isize,
+    pub knockback: f32,
+}
+
+// single path for all damage (hits and damage over time), armor applied in combat.rs
+// direct - hit of a weapon, false for damage over time
+#[derive(Event, Debug)]
+pub struct DamageEvent {
+    pub source: Option<Entity>,
+    pub direct: bool,
+    pub target: Entity,
+    pub damage: i32,
+    pub effects: Vec<DoTStat>,
+    pub interrupt: isize,
+    pub knockback: Vec2,
+}
+
+// health of entity reached zero, killer is the last attacker
+#[derive(Event, Debug)]
+pub struct DeathEvent {
+    pub entity: Entity,
+    pub killer: Option<Entity>,
+}
+
+// room of the map is spawned, every loaded room is one level deeper in the run
+#[derive(Event, Debug)]
+pub struct RoomLoadedEvent;
+
+// entity takes the weapon with the name from assets/weapons, handled in weapon.rs
+#[derive(Event, Debug)]
+pub struct EquipWeaponEvent {
+    pub entity: Entity,
+    pub weapon: String,
+}
+
+// down attack of entity hit an enemy or hazard
+#[derive(Event, Debug)]
+pub struct BounceEvent {
+    pub entity: Entity,
 }
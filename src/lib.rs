@@ -1,17 +1,26 @@
+// systems take many params and queries of tuples, that is the usual shape in bevy
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
 mod combat;
 mod enemy;
 mod entities;
 mod events;
 mod loading;
 mod map;
+mod motor;
 mod player;
 mod ui;
+mod weapon;
 
+use crate::combat::CombatPlugin;
 use crate::enemy::EnemyPlugin;
 use crate::loading::LoadingPlugin;
 use crate::map::MapPlugin;
+use crate::motor::MotorPlugin;
 use crate::player::PlayerPlugin;
+use crate::ui::game_over::GameOverPlugin;
 use crate::ui::menu::MenuPlugin;
+use crate::weapon::WeaponPlugin;
 
 use bevy::app::App;
 
@@ -28,6 +37,7 @@ enum GameState {
     Loading,
     InGame,
     Menu,
+    GameOver,
 }
 
 #[derive(States, Default, Clone, Eq, PartialEq, Debug, Hash)]
@@ -46,9 +56,13 @@ impl Plugin for GamePlugin {
             .add_plugins((
                 LoadingPlugin,
                 MenuPlugin,
+                GameOverPlugin,
                 MapPlugin,
+                MotorPlugin,
                 PlayerPlugin,
                 EnemyPlugin,
+                CombatPlugin,
+                WeaponPlugin,
             ));
 
         #[cfg(debug_assertions)]
@@ -57,7 +71,7 @@ impl Plugin for GamePlugin {
                 FrameTimeDiagnosticsPlugin,
                 LogDiagnosticsPlugin::default(),
                 WorldInspectorPlugin::default(),
-                SystemInformationDiagnosticsPlugin::default(),
+                SystemInformationDiagnosticsPlugin,
             ));
         }
     }
@@ -1,16 +1,23 @@
+use crate::enemy::archetype::EnemyArchetype;
+use crate::weapon::WeaponDefinition;
 use crate::GameState;
 use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
+use bevy_common_assets::ron::RonAssetPlugin;
 
 pub struct LoadingPlugin;
 
 impl Plugin for LoadingPlugin {
     fn build(&self, app: &mut App) {
-        app.add_loading_state(
-            LoadingState::new(GameState::Loading).continue_to_state(GameState::Menu),
-        )
-        .add_collection_to_loading_state::<_, TextureAssets>(GameState::Loading)
-        .add_collection_to_loading_state::<_, PlayerTexture>(GameState::Loading);
+        app.add_plugins(RonAssetPlugin::<EnemyArchetype>::new(&["enemy.ron"]))
+            .add_plugins(RonAssetPlugin::<WeaponDefinition>::new(&["weapon.ron"]))
+            .add_loading_state(
+                LoadingState::new(GameState::Loading).continue_to_state(GameState::Menu),
+            )
+            .add_collection_to_loading_state::<_, TextureAssets>(GameState::Loading)
+            .add_collection_to_loading_state::<_, PlayerTexture>(GameState::Loading)
+            .add_collection_to_loading_state::<_, EnemyAssets>(GameState::Loading)
+            .add_collection_to_loading_state::<_, WeaponAssets>(GameState::Loading);
     }
 }
 
@@ -27,6 +34,20 @@ pub struct PlayerTexture {
     pub sprite: Handle<Image>,
 }
 
+// every enemy type in the folder, designers add files without recompiling
+#[derive(AssetCollection, Resource)]
+pub struct EnemyAssets {
+    #[asset(path = "enemies", collection(typed))]
+    pub archetypes: Vec<Handle<EnemyArchetype>>,
+}
+
+// every weapon in the folder, equipped by the name
+#[derive(AssetCollection, Resource)]
+pub struct WeaponAssets {
+    #[asset(path = "weapons", collection(typed))]
+    pub weapons: Vec<Handle<WeaponDefinition>>,
+}
+
 // #[derive(AssetCollection, Resource)]
 // pub struct SpritePlayer {
 //     #[asset(path="")]
@@ -1,7 +1,12 @@
+pub mod nav;
+
+use crate::entities::{Hazard, HazardDamage, RunEntity};
+use crate::events::RoomLoadedEvent;
 use crate::loading::TextureAssets;
+use crate::map::nav::{build_nav_graph, RoomTiles};
 use crate::GameState;
 
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::Duration};
 use bevy_ecs_tilemap::prelude::*;
 use bevy_rapier2d::prelude::*;
 
@@ -10,16 +15,25 @@ pub struct MapPlugin;
 impl Plugin for MapPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(TilemapPlugin)
-            .add_systems(OnEnter(GameState::InGame), setup_map);
+            .add_event::<RoomLoadedEvent>()
+            .add_systems(OnEnter(GameState::InGame), setup_map)
+            .add_systems(
+                Update,
+                build_nav_graph.run_if(resource_exists_and_changed::<RoomTiles>()),
+            );
     }
 }
 
-fn setup_map(mut commands: Commands, asset: Res<TextureAssets>) {
+fn setup_map(
+    mut commands: Commands,
+    asset: Res<TextureAssets>,
+    mut room_events: EventWriter<RoomLoadedEvent>,
+) {
     let texture_handle = asset.tile.clone(); // dont load tiles in bevy_ecs_tilemap
     let map_size = TilemapSize { x: 21, y: 15 };
 
     let mut tile_storage = TileStorage::empty(map_size);
-    let tilemap_entity = commands.spawn_empty().id();
+    let tilemap_entity = commands.spawn(RunEntity).id();
 
     let mut map = vec![
         [
@@ -90,6 +104,7 @@ fn setup_map(mut commands: Commands, asset: Res<TextureAssets>) {
                     texture_index: idx,
                     ..Default::default()
                 })
+                .insert(RunEntity)
                 .id();
             // Here we let the tile storage component know what tiles we have.
             tile_storage.set(&tile_pos, tile_entity);
@@ -99,12 +114,19 @@ fn setup_map(mut commands: Commands, asset: Res<TextureAssets>) {
                 .insert(RigidBody::Fixed)
                 .insert(TransformBundle::from_transform(Transform::from_xyz(
                     pos_x, pos_y, 0.,
-                )));
+                )))
+                .insert(RunEntity);
             pos_x += colider_block;
         }
         pos_y += colider_block;
         pos_x = 0f32;
     }
+    // navigation of enemies is rebuilt from the tiles of the room
+    commands.insert_resource(RoomTiles {
+        grid: map.iter().map(|row| row.to_vec()).collect(),
+        origin: Vec2::new(0., -400.),
+        tile: colider_block,
+    });
 
     let tile_size = TilemapTileSize { x: 24.0, y: 24.0 };
     let grid_size = TilemapGridSize { x: 24., y: 24. };
@@ -131,11 +153,44 @@ fn setup_map(mut commands: Commands, asset: Res<TextureAssets>) {
             4500.,
             tile_y + (tile_size.y * tile_scale) / 2. - colider_radius,
             0.,
-        )));
+        )))
+        .insert(RunEntity);
+    // spikes on the floor, jumped over or used for the pogo
+    let spikes = Vec2::new(50., 12.);
+    commands
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                color: Color::MAROON,
+                custom_size: Some(spikes * 2.),
+                ..default()
+            },
+            transform: Transform::from_xyz(840., -400. + colider_block / 2. + spikes.y, 10.),
+            ..default()
+        })
+        .insert(Collider::cuboid(spikes.x, spikes.y))
+        .insert(RigidBody::Fixed)
+        .insert(Hazard)
+        .insert(RunEntity)
+        .with_children(|spikes_parent| {
+            // a bit bigger than the spikes to hurt the one standing on them
+            spikes_parent.spawn((
+                TransformBundle::default(),
+                Collider::cuboid(spikes.x, spikes.y + 2.),
+                Sensor,
+                ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_STATIC,
+                HazardDamage {
+                    damage: 10,
+                    knockback: 600.,
+                    cooldown: Timer::new(Duration::from_millis(500), TimerMode::Once),
+                },
+            ));
+        });
     commands
         .spawn(Collider::cuboid(10., 200.))
         .insert(RigidBody::Fixed)
         .insert(TransformBundle::from_transform(Transform::from_xyz(
             -200., 200., 0.,
-        )));
+        )))
+        .insert(RunEntity);
+    room_events.send(RoomLoadedEvent);
 }
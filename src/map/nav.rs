@@ -0,0 +1,390 @@
+use bevy::{prelude::*, utils::Duration, utils::HashMap};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+// tiles of the loaded room, replacing the resource rebuilds the NavGraph
+// grid[y][x] from the bottom row, -1 - empty cell, origin - center of the cell (0, 0)
+#[derive(Resource, Debug, Clone)]
+pub struct RoomTiles {
+    pub grid: Vec<Vec<i32>>,
+    pub origin: Vec2,
+    pub tile: f32,
+}
+
+impl RoomTiles {
+    fn solid(&self, cell: IVec2) -> bool {
+        if cell.x < 0 || cell.y < 0 {
+            return false;
+        }
+        match self.grid.get(cell.y as usize) {
+            Some(row) => row.get(cell.x as usize).is_some_and(|idx| *idx != -1),
+            None => false,
+        }
+    }
+
+    fn width(&self) -> i32 {
+        self.grid.first().map_or(0, |row| row.len() as i32)
+    }
+}
+
+// max height of the jump link in tiles, reached with jump_impulse 1000 (slime)
+const JUMP_TILES: i32 = 2;
+// max horizontal length of the jump link in tiles
+const JUMP_REACH: i32 = 3;
+// extra cost of a jump in tiles, walking around is preferred
+const JUMP_COST: f32 = 1.;
+// free tiles counted above the surface
+const MAX_CLEARANCE: i32 = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+pub enum NavLink {
+    Walk,
+    Jump,
+    Drop,
+}
+
+// walkable surface on top of a solid tile, position - middle of the surface
+// clearance - free tiles above the surface, up to MAX_CLEARANCE
+#[derive(Clone, Debug)]
+pub struct NavNode {
+    pub cell: IVec2,
+    pub position: Vec2,
+    pub clearance: i32,
+    pub links: Vec<(usize, NavLink)>,
+}
+
+// point of the path and the move to reach it from the previous point
+#[derive(Clone, Copy, Debug, Reflect)]
+pub struct NavStep {
+    pub position: Vec2,
+    pub link: NavLink,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct NavGraph {
+    pub nodes: Vec<NavNode>,
+    pub origin: Vec2,
+    pub tile: f32,
+    cells: HashMap<IVec2, usize>,
+}
+
+impl NavGraph {
+    pub fn from_tiles(room: &RoomTiles) -> Self {
+        let mut graph = Self {
+            origin: room.origin,
+            tile: room.tile,
+            ..default()
+        };
+        for y in 0..room.grid.len() as i32 {
+            for x in 0..room.width() {
+                let cell = IVec2::new(x, y);
+                if room.solid(cell) && !room.solid(cell + IVec2::Y) {
+                    graph.cells.insert(cell, graph.nodes.len());
+                    graph.nodes.push(NavNode {
+                        cell,
+                        position: room.origin + Vec2::new(x as f32, y as f32 + 0.5) * room.tile,
+                        clearance: (1..=MAX_CLEARANCE)
+                            .take_while(|dy| !room.solid(cell + IVec2::new(0, *dy)))
+                            .count() as i32,
+                        links: Vec::new(),
+                    });
+                }
+            }
+        }
+
+        for index in 0..graph.nodes.len() {
+            let cell = graph.nodes[index].cell;
+            let mut links = Vec::new();
+            for side in [-1, 1] {
+                let next = cell + IVec2::new(side, 0);
+                if let Some(&other) = graph.cells.get(&next) {
+                    links.push((other, NavLink::Walk));
+                } else if !room.solid(next) && !room.solid(next + IVec2::Y) {
+                    // ledge, fall to the first surface below
+                    if let Some(other) = (0..cell.y)
+                        .rev()
+                        .find_map(|y| graph.cells.get(&IVec2::new(next.x, y)))
+                    {
+                        links.push((*other, NavLink::Drop));
+                    }
+                }
+            }
+            for (&other_cell, &other) in graph.cells.iter() {
+                let offset = other_cell - cell;
+                let gap = offset.y == 0 && offset.x.abs() > 1;
+                if offset.y < 0 || offset.y > JUMP_TILES || offset.x.abs() > JUMP_REACH {
+                    continue;
+                }
+                if !(gap || offset.y > 0) {
+                    continue;
+                }
+                // head room over the take off
+                let blocked = (1..=offset.y + 1).any(|dy| room.solid(cell + IVec2::new(0, dy)));
+                if !blocked {
+                    links.push((other, NavLink::Jump));
+                }
+            }
+            graph.nodes[index].links = links;
+        }
+        graph
+    }
+
+    // surface under the position, neighbour columns for entities standing on the edge
+    pub fn node_at(&self, position: Vec2) -> Option<usize> {
+        let cell = ((position - self.origin) / self.tile).round().as_ivec2();
+        [0, -1, 1].iter().find_map(|dx| {
+            (0..=cell.y)
+                .rev()
+                .find_map(|y| self.cells.get(&IVec2::new(cell.x + dx, y)).copied())
+        })
+    }
+
+    // is there ground in front, used to turn around on ledges
+    pub fn walkable_ahead(&self, position: Vec2, direction: f32) -> bool {
+        let Some(node) = self.node_at(position) else {
+            return true;
+        };
+        let ahead = self.nodes[node].cell + IVec2::new(direction.signum() as i32, 0);
+        self.cells.contains_key(&ahead)
+    }
+
+    // A* from the surface under from to the surface under to, without the start point
+    pub fn find_path(&self, from: Vec2, to: Vec2, can_jump: bool) -> Option<Vec<NavStep>> {
+        let start = self.node_at(from)?;
+        let goal = self.node_at(to)?;
+        let goal_position = self.nodes[goal].position;
+
+        let mut open = BinaryHeap::new();
+        let mut came_from: HashMap<usize, (usize, NavLink)> = HashMap::default();
+        let mut cost: HashMap<usize, f32> = HashMap::default();
+        cost.insert(start, 0.);
+        open.push(OpenNode {
+            node: start,
+            score: 0.,
+        });
+
+        while let Some(OpenNode { node, .. }) = open.pop() {
+            if node == goal {
+                let mut steps = Vec::new();
+                let mut current = goal;
+                while let Some(&(previous, link)) = came_from.get(&current) {
+                    steps.push(NavStep {
+                        position: self.nodes[current].position,
+                        link,
+                    });
+                    current = previous;
+                }
+                steps.reverse();
+                return Some(steps);
+            }
+            let current_cost = cost[&node];
+            for &(next, link) in self.nodes[node].links.iter() {
+                if link == NavLink::Jump && !can_jump {
+                    continue;
+                }
+                let mut step_cost = self.nodes[node]
+                    .position
+                    .distance(self.nodes[next].position);
+                if link == NavLink::Jump {
+                    step_cost += JUMP_COST * self.tile;
+                }
+                let next_cost = current_cost + step_cost;
+                if next_cost < cost.get(&next).copied().unwrap_or(f32::INFINITY) {
+                    cost.insert(next, next_cost);
+                    came_from.insert(next, (node, link));
+                    open.push(OpenNode {
+                        node: next,
+                        score: next_cost + self.nodes[next].position.distance(goal_position),
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+// min-heap entry of A*
+struct OpenNode {
+    node: usize,
+    score: f32,
+}
+
+impl PartialEq for OpenNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.score == other.score
+    }
+}
+
+impl Eq for OpenNode {}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.score.total_cmp(&self.score)
+    }
+}
+
+// distance to the point of the path to count as reached
+const STEP_REACHED: f32 = 20.;
+
+// path of the walking enemy, searched again every repath
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct NavPath {
+    pub steps: Vec<NavStep>,
+    pub repath: Timer,
+}
+
+impl NavPath {
+    pub fn new(repath: Duration) -> Self {
+        let mut repath = Timer::new(repath, TimerMode::Once);
+        repath.tick(repath.duration()); // search on the first request
+        Self {
+            steps: Vec::new(),
+            repath,
+        }
+    }
+
+    // next point on the way to the goal
+    pub fn next(
+        &mut self,
+        graph: &NavGraph,
+        from: Vec2,
+        goal: Vec2,
+        can_jump: bool,
+        delta: Duration,
+    ) -> Option<NavStep> {
+        self.repath.tick(delta);
+        if self.repath.finished() {
+            self.repath.reset();
+            self.steps = graph.find_path(from, goal, can_jump).unwrap_or_default();
+        }
+        while let Some(step) = self.steps.first() {
+            let reached = (step.position.x - from.x).abs() < STEP_REACHED
+                && (step.position.y - from.y).abs() < graph.tile;
+            if !reached {
+                break;
+            }
+            self.steps.remove(0);
+        }
+        self.steps.first().copied()
+    }
+
+    // path leaves the current surface, walking straight to the goal is not enough
+    pub fn changes_level(&self) -> bool {
+        self.steps.iter().any(|step| step.link != NavLink::Walk)
+    }
+}
+
+pub fn build_nav_graph(mut commands: Commands, room: Res<RoomTiles>) {
+    commands.insert_resource(NavGraph::from_tiles(&room));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TILE: f32 = 100.;
+
+    // rows from the bottom
+    fn room(rows: &[&[i32]]) -> RoomTiles {
+        RoomTiles {
+            grid: rows.iter().map(|row| row.to_vec()).collect(),
+            origin: Vec2::ZERO,
+            tile: TILE,
+        }
+    }
+
+    // floor on the left and a platform two tiles up on the right
+    fn floor_and_platform() -> NavGraph {
+        NavGraph::from_tiles(&room(&[
+            &[1, 1, 1, -1, -1],
+            &[-1, -1, -1, -1, -1],
+            &[-1, -1, -1, 1, 1],
+        ]))
+    }
+
+    // point standing on the surface of the cell
+    fn above(x: i32, y: i32) -> Vec2 {
+        Vec2::new(x as f32, y as f32 + 1.) * TILE
+    }
+
+    #[test]
+    fn nodes_on_top_of_solid_tiles() {
+        let graph = floor_and_platform();
+        let mut cells: Vec<IVec2> = graph.nodes.iter().map(|node| node.cell).collect();
+        cells.sort_by_key(|cell| (cell.y, cell.x));
+        assert_eq!(
+            cells,
+            vec![
+                IVec2::new(0, 0),
+                IVec2::new(1, 0),
+                IVec2::new(2, 0),
+                IVec2::new(3, 2),
+                IVec2::new(4, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn clearance_stops_at_the_ceiling() {
+        let graph = NavGraph::from_tiles(&room(&[&[1, 1], &[-1, -1], &[-1, -1], &[1, -1]]));
+        let clearance = |x: i32| {
+            graph
+                .nodes
+                .iter()
+                .find(|node| node.cell == IVec2::new(x, 0))
+                .map(|node| node.clearance)
+        };
+        assert_eq!(clearance(0), Some(2));
+        assert_eq!(clearance(1), Some(MAX_CLEARANCE));
+    }
+
+    #[test]
+    fn walk_on_the_same_surface() {
+        let graph = floor_and_platform();
+        let path = graph.find_path(above(0, 0), above(2, 0), false).unwrap();
+        assert_eq!(path.len(), 2);
+        assert!(path.iter().all(|step| step.link == NavLink::Walk));
+        assert_eq!(path.last().unwrap().position, Vec2::new(200., 50.));
+    }
+
+    #[test]
+    fn platform_needs_a_jump() {
+        let graph = floor_and_platform();
+        assert!(graph.find_path(above(0, 0), above(4, 2), false).is_none());
+        let path = graph.find_path(above(0, 0), above(4, 2), true).unwrap();
+        assert!(path.iter().any(|step| step.link == NavLink::Jump));
+        assert_eq!(path.last().unwrap().position, Vec2::new(400., 250.));
+    }
+
+    #[test]
+    fn drop_from_the_platform_without_jumps() {
+        let graph = floor_and_platform();
+        let path = graph.find_path(above(4, 2), above(0, 0), false).unwrap();
+        assert!(path.iter().any(|step| step.link == NavLink::Drop));
+        assert!(path.iter().all(|step| step.link != NavLink::Jump));
+        assert_eq!(path.last().unwrap().position, Vec2::new(0., 50.));
+    }
+
+    #[test]
+    fn no_ground_ahead_on_the_ledge() {
+        let graph = floor_and_platform();
+        assert!(graph.walkable_ahead(above(1, 0), 1.));
+        assert!(!graph.walkable_ahead(above(2, 0), 1.));
+        assert!(!graph.walkable_ahead(above(3, 2), -1.));
+    }
+
+    #[test]
+    fn path_is_ready_on_the_first_request() {
+        let graph = floor_and_platform();
+        let mut path = NavPath::new(Duration::from_millis(500));
+        let step = path.next(&graph, above(0, 0), above(2, 0), false, Duration::ZERO);
+        assert_eq!(step.map(|step| step.position), Some(Vec2::new(100., 50.)));
+    }
+}
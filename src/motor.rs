@@ -0,0 +1,133 @@
+use crate::entities::{Dead, Impulse};
+use crate::{GameState, InGameState};
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+pub struct MotorPlugin;
+
+impl Plugin for MotorPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(
+            Update,
+            (MotorSet::Sense, MotorSet::Input, MotorSet::Integrate).chain(),
+        )
+        .add_systems(Update, sense_ground.in_set(MotorSet::Sense))
+        .add_systems(Update, integrate_motor.in_set(MotorSet::Integrate));
+        // conditions go to every set, a chain of sets can't take them
+        for set in [MotorSet::Sense, MotorSet::Input, MotorSet::Integrate] {
+            app.configure_set(
+                Update,
+                set.run_if(in_state(GameState::InGame))
+                    .run_if(in_state(InGameState::Play)),
+            );
+        }
+    }
+}
+
+// movement systems of the player and enemies go to Input, between reading the ground and integration
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
+pub enum MotorSet {
+    Sense,
+    Input,
+    Integrate,
+}
+
+// kinematic movement of a character, the entity only fills input for the current frame
+// input - velocity added this frame (walk, jump), acceleration - extra acceleration this frame (wall slide)
+// friction - multiplier of horizontal velocity on the ground, air_friction - in the air
+// gravity_scale - 0. for flying entities, max_speed - clamp of every axis
+#[derive(Clone, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct CharacterMotor {
+    pub input: Vec2,
+    pub acceleration: Vec2,
+    pub velocity: Vec2,
+    pub grounded: bool,
+    pub friction: f32,
+    pub air_friction: f32,
+    pub gravity_scale: f32,
+    pub max_speed: f32,
+}
+
+impl Default for CharacterMotor {
+    fn default() -> Self {
+        Self {
+            input: Vec2::ZERO,
+            acceleration: Vec2::ZERO,
+            velocity: Vec2::ZERO,
+            grounded: false,
+            friction: 0.9,
+            air_friction: 0.95,
+            gravity_scale: 1.,
+            max_speed: 1000.,
+        }
+    }
+}
+
+impl CharacterMotor {
+    // jump drops the falling inertia
+    pub fn jump(&mut self, impulse: f32) {
+        self.velocity.y = 0.;
+        self.input.y += impulse;
+    }
+}
+
+fn sense_ground(
+    mut motor_q: Query<(
+        &mut CharacterMotor,
+        Option<&KinematicCharacterControllerOutput>,
+    )>,
+) {
+    for (mut motor, controller_output) in motor_q.iter_mut() {
+        motor.grounded = match controller_output {
+            Some(out) => out.grounded,
+            None => false,
+        };
+    }
+}
+
+fn integrate_motor(
+    time: Res<Time>,
+    rapier_config: Res<RapierConfiguration>,
+    mut motor_q: Query<
+        (
+            &mut CharacterMotor,
+            &mut KinematicCharacterController,
+            Option<&mut Impulse>,
+        ),
+        Without<Dead>,
+    >,
+) {
+    let dt = time.delta_seconds();
+    for (mut motor, mut controller, impulse) in motor_q.iter_mut() {
+        let mut acceleration = motor.acceleration;
+        let mut velocity = motor.velocity;
+
+        if motor.grounded {
+            velocity.x *= motor.friction;
+        } else {
+            velocity.x *= motor.air_friction;
+            acceleration += Vec2::Y * rapier_config.gravity * motor.gravity_scale;
+        }
+        // without gravity nothing stops vertical movement, damp it like horizontal
+        if motor.gravity_scale == 0. {
+            velocity.y *= motor.air_friction;
+        }
+
+        velocity += motor.input;
+        if let Some(mut impulse) = impulse {
+            velocity += impulse.0;
+            impulse.0 = Vec2::ZERO;
+        }
+        velocity = velocity.clamp(Vec2::splat(-motor.max_speed), Vec2::splat(motor.max_speed));
+
+        motor.velocity = (acceleration * dt) + velocity;
+        motor.input = Vec2::ZERO;
+        motor.acceleration = Vec2::ZERO;
+
+        // dash and other systems may have already moved the controller this frame
+        let translation = controller.translation.unwrap_or(Vec2::ZERO) + motor.velocity * dt;
+        controller.translation = Some(translation);
+    }
+}
@@ -0,0 +1,345 @@
+use crate::combat::{spawn_hitbox, spawn_projectile};
+use crate::entities::*;
+use crate::events::EquipWeaponEvent;
+use crate::loading::PlayerTexture;
+use crate::player::{Player, PlayerActions, PlayerStates};
+
+use bevy::{prelude::*, utils::Duration};
+use bevy_rapier2d::prelude::*;
+use leafwing_input_manager::prelude::*;
+use rand::seq::SliceRandom;
+
+// class picked in the menu, applied when the player is spawned
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct SelectedClass(pub PlayerClass);
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash, Component, Reflect)]
+#[reflect(Component)]
+pub enum PlayerClass {
+    #[default]
+    Warrior,
+    Wizard,
+    Archer,
+}
+
+// base stats, starting equipment and look of the class
+// weapon - name of the weapon in assets/weapons
+// ranged - bow of the class, side attacks fire it instead of the melee swing
+// texture - sprite sheet of the class in assets, None for the adventurer sheet
+// color - tint of the player sprite
+pub struct ClassDefinition {
+    pub name: &'static str,
+    pub health: i32,
+    pub armor: i32,
+    pub strength: i32,
+    pub speed: f32,
+    pub weapon: &'static str,
+    pub ranged: Option<RangedWeapon>,
+    pub ability: Ability,
+    pub texture: Option<&'static str>,
+    pub color: Color,
+}
+
+impl ClassDefinition {
+    pub fn texture(&self, asset_server: &AssetServer, default: &PlayerTexture) -> Handle<Image> {
+        match self.texture {
+            Some(path) => asset_server.load(path),
+            None => default.sprite.clone(),
+        }
+    }
+}
+
+impl PlayerClass {
+    pub const ALL: [PlayerClass; 3] = [
+        PlayerClass::Warrior,
+        PlayerClass::Wizard,
+        PlayerClass::Archer,
+    ];
+
+    pub fn definition(&self) -> ClassDefinition {
+        match self {
+            PlayerClass::Warrior => ClassDefinition {
+                name: "Warrior",
+                health: 130,
+                armor: 3,
+                strength: 10,
+                speed: 54.,
+                weapon: "Sword",
+                ranged: None,
+                ability: Ability::Charge,
+                texture: None,
+                color: Color::WHITE,
+            },
+            PlayerClass::Wizard => ClassDefinition {
+                name: "Wizard",
+                health: 80,
+                armor: 0,
+                strength: 12,
+                speed: 58.,
+                weapon: "Staff",
+                ranged: None,
+                ability: Ability::Fireball,
+                texture: None,
+                color: Color::rgb(0.6, 0.6, 1.),
+            },
+            PlayerClass::Archer => ClassDefinition {
+                name: "Archer",
+                health: 100,
+                armor: 2,
+                strength: 9,
+                speed: 64.,
+                weapon: "Dagger",
+                ranged: Some(RangedWeapon {
+                    damage: Damage(1),
+                    speed: 1100.,
+                    gravity: 0.3,
+                    color: Color::BEIGE,
+                    ..default()
+                }),
+                ability: Ability::Volley,
+                texture: None,
+                color: Color::rgb(0.6, 1., 0.6),
+            },
+        }
+    }
+}
+
+// signature abilities of the classes
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Reflect)]
+pub enum Ability {
+    // rush forward with a shield bash that stuns and throws enemies away
+    #[default]
+    Charge,
+    // straight fireball that sets the target on fire
+    Fireball,
+    // fan of arrows from the bow
+    Volley,
+}
+
+impl Ability {
+    fn cooldown(&self) -> Duration {
+        match self {
+            Ability::Charge => Duration::from_secs(3),
+            Ability::Fireball => Duration::from_secs(2),
+            Ability::Volley => Duration::from_secs(4),
+        }
+    }
+}
+
+// ability used with the Ability action, ready again when the cooldown is finished
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct ClassAbility {
+    pub ability: Ability,
+    pub cooldown: Timer,
+}
+
+impl ClassAbility {
+    pub fn new(ability: Ability) -> Self {
+        let mut cooldown = Timer::new(ability.cooldown(), TimerMode::Once);
+        cooldown.tick(ability.cooldown()); // ready on spawn
+        Self { ability, cooldown }
+    }
+}
+
+const CHARGE_IMPULSE: f32 = 1600.;
+// distance to the point the projectiles of abilities are aimed at
+const ABILITY_RANGE: f32 = 600.;
+// angles of the arrows of the volley in radians
+const VOLLEY_ANGLES: [f32; 3] = [-0.15, 0., 0.15];
+
+fn shield_bash() -> Weapon {
+    Weapon {
+        name: "Shield Bash".into(),
+        damage: Damage(2),
+        attack_time: Duration::from_millis(300),
+        hitbox: Vec2::new(8., 12.),
+        offset: Vec2::new(10., 0.),
+        interrupt: 800,
+        knockback: 1200.,
+        effects: Vec::new(),
+        combo: Vec::new(),
+        combo_window: Duration::ZERO,
+    }
+}
+
+fn fireball() -> RangedWeapon {
+    RangedWeapon {
+        damage: Damage(4),
+        speed: 700.,
+        size: Vec2::new(14., 14.),
+        interrupt: 300,
+        knockback: 500.,
+        effects: vec![DoTStat::burn()],
+        color: Color::ORANGE_RED,
+        ..default()
+    }
+}
+
+pub(super) fn player_ability(
+    mut commands: Commands,
+    time: Res<Time>,
+    rapier_config: Res<RapierConfiguration>,
+    mut player_q: Query<
+        (
+            Entity,
+            &ActionState<PlayerActions>,
+            &ActiveEntity<PlayerStates>,
+            &mut ClassAbility,
+            &mut Impulse,
+            &Transform,
+            Option<&RangedWeapon>,
+            Option<&Stunned>,
+        ),
+        (With<Player>, Without<Dead>),
+    >,
+) {
+    let Ok((entity, action_state, player, mut ability, mut impulse, transform, bow, stun)) =
+        player_q.get_single_mut()
+    else {
+        return;
+    };
+    ability.cooldown.tick(time.delta());
+    if stun.is_some()
+        || !ability.cooldown.finished()
+        || !action_state.just_pressed(PlayerActions::Ability)
+    {
+        return;
+    }
+    ability.cooldown.reset();
+
+    let forward = Vec2::X * player.rotation as f32;
+    let origin = transform.translation.truncate();
+    match ability.ability {
+        Ability::Charge => {
+            impulse.0 += forward * CHARGE_IMPULSE;
+            spawn_hitbox(
+                &mut commands,
+                entity,
+                &shield_bash(),
+                player.rotation,
+                AttackDirection::Side,
+            );
+        }
+        Ability::Fireball => {
+            spawn_projectile(
+                &mut commands,
+                entity,
+                Faction::Player,
+                &fireball(),
+                origin,
+                origin + forward * ABILITY_RANGE,
+                rapier_config.gravity,
+            );
+        }
+        Ability::Volley => {
+            let bow = bow.cloned().unwrap_or_default();
+            for angle in VOLLEY_ANGLES {
+                let direction = Vec2::from_angle(angle * player.rotation as f32).rotate(forward);
+                spawn_projectile(
+                    &mut commands,
+                    entity,
+                    Faction::Player,
+                    &bow,
+                    origin,
+                    origin + direction * ABILITY_RANGE,
+                    rapier_config.gravity,
+                );
+            }
+        }
+    }
+}
+
+// pickups with the name are class change items
+const CLASS_RUNE: &str = "rune";
+
+// item changing the class of the player on pick up
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct ClassItem(pub PlayerClass);
+
+// rune gets a random class other than the current one and its color
+pub(super) fn setup_class_items(
+    mut commands: Commands,
+    mut rune_q: Query<(Entity, &Pickup, &mut Sprite), Added<Pickup>>,
+    player_q: Query<&PlayerClass, With<Player>>,
+) {
+    let current = player_q.get_single().ok().copied();
+    for (entity, pickup, mut sprite) in rune_q.iter_mut() {
+        if pickup.0 != CLASS_RUNE {
+            continue;
+        }
+        let classes: Vec<PlayerClass> = PlayerClass::ALL
+            .into_iter()
+            .filter(|class| Some(*class) != current)
+            .collect();
+        let Some(class) = classes.choose(&mut rand::thread_rng()).copied() else {
+            continue;
+        };
+        sprite.color = class.definition().color;
+        commands.entity(entity).insert(ClassItem(class));
+    }
+}
+
+// change of the class keeps everything the player got during the run:
+// Health, Armor, Strength and Speed stay the values of the starting class,
+// StatModifiers (artifacts, buffs, debuffs), DoTs and shields stay as they are
+// weapon, bow, ability and look come from the new class, the new ability starts on cooldown
+// and the combo chain is reset by the equip of the weapon
+pub(super) fn collect_class_items(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut equip_events: EventWriter<EquipWeaponEvent>,
+    asset_server: Res<AssetServer>,
+    player_texture: Res<PlayerTexture>,
+    item_q: Query<&ClassItem, Without<Player>>,
+    mut player_q: Query<
+        (
+            Entity,
+            &mut PlayerClass,
+            &mut ClassAbility,
+            &mut Sprite,
+            &mut Handle<Image>,
+        ),
+        (With<Player>, Without<Dead>),
+    >,
+) {
+    let Ok((entity, mut class, mut ability, mut sprite, mut texture)) = player_q.get_single_mut()
+    else {
+        return;
+    };
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(first, second, _) = collision_event else {
+            continue;
+        };
+        let item = match (*first, *second) {
+            (player, item) | (item, player) if player == entity => item,
+            _ => continue,
+        };
+        let Ok(class_item) = item_q.get(item) else {
+            continue;
+        };
+        if class_item.0 == *class {
+            continue;
+        }
+        commands.entity(item).despawn_recursive();
+
+        let definition = class_item.0.definition();
+        *class = class_item.0;
+        equip_events.send(EquipWeaponEvent {
+            entity,
+            weapon: definition.weapon.into(),
+        });
+        *ability = ClassAbility::new(definition.ability);
+        ability.cooldown.reset();
+        sprite.color = definition.color;
+        *texture = definition.texture(&asset_server, &player_texture);
+        match definition.ranged {
+            Some(bow) => commands.entity(entity).insert(bow),
+            None => commands.entity(entity).remove::<RangedWeapon>(),
+        };
+        // one class per frame
+        break;
+    }
+}
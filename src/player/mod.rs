@@ -1,5 +1,14 @@
+pub mod class;
+
+use crate::combat::{spawn_hitbox, spawn_projectile, DeathSettings};
 use crate::entities::*;
-use crate::loading::PlayerTexture;
+use crate::events::{BounceEvent, DeathEvent};
+use crate::loading::{PlayerTexture, WeaponAssets};
+use crate::motor::{CharacterMotor, MotorSet};
+use crate::player::class::{
+    collect_class_items, player_ability, setup_class_items, ClassAbility, SelectedClass,
+};
+use crate::weapon::WeaponDefinition;
 use crate::{GameState, InGameState};
 
 use bevy::{prelude::*, utils::Duration};
@@ -21,18 +30,28 @@ impl Plugin for PlayerPlugin {
             gravity: Vec2::new(0.0, -2000.0),
             ..default()
         })
+        .init_resource::<SelectedClass>()
+        .init_resource::<JumpSettings>()
+        .init_resource::<JumpInfo>()
         .add_event::<StopJump>()
         .add_event::<SlideEvent>()
-        .add_systems(OnEnter(GameState::InGame), (spawn_player, spawn_camera))
+        .add_systems(
+            OnEnter(GameState::InGame),
+            (spawn_player, spawn_camera, reset_jump),
+        )
         .add_systems(
             Update,
             (
-                move_player,
+                move_player.in_set(MotorSet::Input),
                 player_attack,
+                player_ability,
+                // the rune gets its class before the collision with it is handled
+                (setup_class_items, apply_deferred, collect_class_items).chain(),
                 follow,
                 camera_settings,
                 player_collision,
-                sensor_event,
+                player_death,
+                game_over,
             )
                 .run_if(in_state(GameState::InGame))
                 .run_if(in_state(InGameState::Play)), // TODO: this func and action press to pause.rs on main crate
@@ -44,31 +63,72 @@ impl Plugin for PlayerPlugin {
 const DASH_FRAMES: u8 = 7;
 const DASH_SPEED_FACTOR: f32 = 0.1;
 const MAX_JUMP: u8 = 2;
-
+const POGO_IMPULSE: f32 = 900.;
+// stick tilt to choose up or down attack
+const ATTACK_AXIS_DEADZONE: f32 = 0.5;
+// distance to the point the bow is aimed at
+const BOW_RANGE: f32 = 800.;
+
+// the ground jump is still available for a while after walking off a ledge
+pub const COYOTE_TIME: Duration = Duration::from_millis(100);
+// jump pressed when no jump is available is done if one becomes available in time (landing)
+pub const JUMP_BUFFER: Duration = Duration::from_millis(120);
+
+// windows of the coyote time and the jump buffer, a run starts with them
 #[derive(Resource, Debug, Reflect)]
 #[reflect(Resource)]
-struct TimeAttack(Timer);
+pub struct JumpSettings {
+    pub coyote_time: Duration,
+    pub buffer: Duration,
+}
 
-impl Default for TimeAttack {
+impl Default for JumpSettings {
     fn default() -> Self {
-        Self(Timer::new(Duration::from_millis(380), TimerMode::Once))
+        Self {
+            coyote_time: COYOTE_TIME,
+            buffer: JUMP_BUFFER,
+        }
     }
 }
 
+// coyote_time and buffer - windows of JumpSettings
+// windows are timers, so they don't depend on the frame rate
 #[derive(Resource, Debug, Reflect)]
 #[reflect(Resource)]
 struct JumpInfo {
     count: u8,
     time_up: Timer,
+    coyote_time: Timer,
+    buffer: Timer,
 }
 
-impl Default for JumpInfo {
-    fn default() -> Self {
+impl JumpInfo {
+    fn new(coyote_time: Duration, buffer: Duration) -> Self {
+        let mut buffer = Timer::new(buffer, TimerMode::Once);
+        buffer.tick(buffer.duration()); // nothing buffered on init
         Self {
             count: 0,
             time_up: Timer::new(Duration::from_millis(500), TimerMode::Once),
+            coyote_time: Timer::new(coyote_time, TimerMode::Once),
+            buffer,
         }
     }
+
+    fn clear_buffer(&mut self) {
+        let buffer = self.buffer.duration();
+        self.buffer.tick(buffer);
+    }
+}
+
+impl Default for JumpInfo {
+    fn default() -> Self {
+        Self::new(COYOTE_TIME, JUMP_BUFFER)
+    }
+}
+
+// jumps, coyote time and buffer of the previous run are dropped
+fn reset_jump(mut jump_info: ResMut<JumpInfo>, settings: Res<JumpSettings>) {
+    *jump_info = JumpInfo::new(settings.coyote_time, settings.buffer);
 }
 
 #[derive(Event, Default, Debug)]
@@ -99,17 +159,32 @@ impl Default for DashInfo {
         cooldown_timer.tick(Duration::from_secs_f32(1.5)); // finished on init
         Self {
             cooldown_time: cooldown_timer,
-            evade_time: Timer::new(Duration::from_millis(50), TimerMode::Once),
+            evade_time: Timer::new(Duration::from_millis(50), TimerMode::Once), // i-frames
             is_used: false,
             frames_count: 0,
         }
     }
 }
 
+impl DashInfo {
+    // dash is over or cancelled by a jump, the cooldown starts
+    fn finish(&mut self) {
+        if self.is_used {
+            self.is_used = false;
+            self.cooldown_time.reset();
+        }
+    }
+}
+
 #[derive(Clone, Default, Debug, Component, Reflect)]
 #[reflect(Component)]
 pub struct Player(pub Name);
 
+// time between player death and game over screen
+#[derive(Clone, Default, Debug, Component, Reflect)]
+#[reflect(Component)]
+struct GameOverTimer(Timer);
+
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Default, Reflect)]
 enum PlayerStates {
     #[default]
@@ -117,6 +192,7 @@ enum PlayerStates {
     Run,
     Jump,
     Fall,
+    Death,
 }
 
 #[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug, Reflect)]
@@ -126,6 +202,7 @@ enum PlayerActions {
     Dash,
     Save,
     Attack,
+    Ability,
     Pause,
 }
 
@@ -143,7 +220,8 @@ fn spawn_camera(mut commands: Commands) {
                 .insert(SingleAxis::mouse_wheel_y(), CameraActions::Zoom)
                 .build(),
             ..default()
-        });
+        })
+        .insert(RunEntity);
 }
 fn camera_settings(
     mut q: Query<(&mut OrthographicProjection, &ActionState<CameraActions>), With<Camera2d>>,
@@ -167,64 +245,106 @@ struct PlayerBundle {
     rigid_body: RigidBody,
     controller: KinematicCharacterController,
     controller_output: KinematicCharacterControllerOutput,
+    motor: CharacterMotor,
     collider: Collider,
     attack: AttackCollider,
+    weapon: Weapon,
+    impulse: Impulse,
+    faction: Faction,
+    combo: Combo,
+    hit_invulnerability: HitInvulnerability,
 }
 
-fn spawn_player(mut commands: Commands, texture: Res<PlayerTexture>) {
-    commands
-        .spawn(PlayerBundle {
-            name: Player(Name::new("Player")),
-            player: ActiveEntity {
-                rotation: 1,
-                velocity: Vec2::default(),
-                current_state: PlayerStates::default(),
-            },
-            stats: Stats {
-                health: Health::default(),
-                armor: Armor(10),
-                strength: Strength(10),
-            },
-            sprite: SpriteBundle {
-                texture: texture.sprite.clone(),
-                transform: Transform::from_xyz(-100., 400., 10.).with_scale(Vec3::splat(4.)), // 50 x 37  * 4 200 x 148
-                ..Default::default()
-            },
-            input: InputManagerBundle::<PlayerActions> {
-                action_state: ActionState::default(),
-                input_map: InputMap::default()
-                    .insert(KeyCode::Escape, PlayerActions::Save) // delete this
-                    .insert(DualAxis::left_stick(), PlayerActions::Move)
-                    .insert(VirtualDPad::wasd(), PlayerActions::Move)
-                    .insert(VirtualDPad::arrow_keys(), PlayerActions::Move)
-                    .insert(KeyCode::Space, PlayerActions::Jump)
-                    .insert(GamepadButtonType::South, PlayerActions::Jump)
-                    .insert(KeyCode::ShiftLeft, PlayerActions::Dash)
-                    .insert(GamepadButtonType::East, PlayerActions::Dash)
-                    .insert(MouseButton::Left, PlayerActions::Attack)
-                    .insert(KeyCode::Escape, PlayerActions::Pause)
-                    .set_gamepad(Gamepad { id: 0 })
-                    .build(),
-            },
-            // rigid_body: RigidBody::Dynamic,
-            // rigid_body: RigidBody::KinematicPositionBased,
-            rigid_body: RigidBody::KinematicVelocityBased,
-            controller: KinematicCharacterController {
-                slide: true,
-                autostep: None,
-                filter_flags: QueryFilterFlags::EXCLUDE_KINEMATIC
-                    | QueryFilterFlags::EXCLUDE_SENSORS,
+fn spawn_player(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    texture: Res<PlayerTexture>,
+    weapon_assets: Res<WeaponAssets>,
+    weapons: Res<Assets<WeaponDefinition>>,
+    selected_class: Res<SelectedClass>,
+) {
+    let class = selected_class.0;
+    let definition = class.definition();
+    let weapon = weapon_assets
+        .weapon(&weapons, definition.weapon)
+        .unwrap_or_default();
+    let mut player = commands.spawn(PlayerBundle {
+        name: Player(Name::new("Player")),
+        player: ActiveEntity {
+            rotation: 1,
+            current_state: PlayerStates::default(),
+        },
+        stats: Stats {
+            health: Health(definition.health),
+            armor: Armor(definition.armor),
+            strength: Strength(definition.strength),
+            speed: Speed(definition.speed),
+            ..default()
+        },
+        sprite: SpriteBundle {
+            sprite: Sprite {
+                color: definition.color,
                 ..default()
             },
-            controller_output: KinematicCharacterControllerOutput::default(),
-            collider: Collider::capsule(Vec2::new(0., 8.), Vec2::new(0.0, -11.0), 7.),
-
-            attack: AttackCollider(None),
-        })
-        .insert(ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_KINEMATIC);
+            texture: definition.texture(&asset_server, &texture),
+            transform: Transform::from_xyz(-100., 400., 10.).with_scale(Vec3::splat(4.)), // 50 x 37  * 4 200 x 148
+            ..Default::default()
+        },
+        input: InputManagerBundle::<PlayerActions> {
+            action_state: ActionState::default(),
+            input_map: InputMap::default()
+                .insert(KeyCode::Escape, PlayerActions::Save) // delete this
+                .insert(DualAxis::left_stick(), PlayerActions::Move)
+                .insert(VirtualDPad::wasd(), PlayerActions::Move)
+                .insert(VirtualDPad::arrow_keys(), PlayerActions::Move)
+                .insert(KeyCode::Space, PlayerActions::Jump)
+                .insert(GamepadButtonType::South, PlayerActions::Jump)
+                .insert(KeyCode::ShiftLeft, PlayerActions::Dash)
+                .insert(GamepadButtonType::East, PlayerActions::Dash)
+                .insert(MouseButton::Left, PlayerActions::Attack)
+                .insert(MouseButton::Right, PlayerActions::Ability)
+                .insert(KeyCode::E, PlayerActions::Ability)
+                .insert(GamepadButtonType::West, PlayerActions::Ability)
+                .insert(KeyCode::Escape, PlayerActions::Pause)
+                .set_gamepad(Gamepad { id: 0 })
+                .build(),
+        },
+        // rigid_body: RigidBody::Dynamic,
+        // rigid_body: RigidBody::KinematicPositionBased,
+        rigid_body: RigidBody::KinematicVelocityBased,
+        controller: KinematicCharacterController {
+            slide: true,
+            autostep: None,
+            filter_flags: QueryFilterFlags::EXCLUDE_KINEMATIC | QueryFilterFlags::EXCLUDE_SENSORS,
+            ..default()
+        },
+        controller_output: KinematicCharacterControllerOutput::default(),
+        motor: CharacterMotor {
+            air_friction: 0.9,
+            ..default()
+        },
+        collider: Collider::capsule(Vec2::new(0., 8.), Vec2::new(0.0, -11.0), 7.),
+
+        attack: AttackCollider(None),
+        weapon,
+        impulse: Impulse::default(),
+        faction: Faction::Player,
+        combo: Combo::default(),
+        hit_invulnerability: HitInvulnerability(Duration::from_millis(600)),
+    });
+    player.insert((
+        ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_KINEMATIC,
+        class,
+        ClassAbility::new(definition.ability),
+        Inventory::default(),
+        RunEntity,
+    ));
+    if let Some(bow) = definition.ranged {
+        player.insert(bow);
+    }
 }
 
-#[derive(Resource, Debug, Reflect)]
+#[derive(Resource, Debug, Default, Reflect)]
 #[reflect(Resource)]
 struct Pause(bool);
 
@@ -238,12 +358,6 @@ impl Pause {
     }
 }
 
-impl Default for Pause {
-    fn default() -> Self {
-        Self(false)
-    }
-}
-
 fn pause(
     mut state: ResMut<NextState<InGameState>>,
     q: Query<&ActionState<PlayerActions>>,
@@ -264,115 +378,141 @@ fn pause(
 }
 
 fn move_player(
+    mut commands: Commands,
     time: Res<Time>,
-    rapier_config: Res<RapierConfiguration>,
-    mut jump_info: Local<JumpInfo>,
+    mut jump_info: ResMut<JumpInfo>,
     mut dash_info: Local<DashInfo>,
     mut controller_query: Query<
         (
+            Entity,
             &ActionState<PlayerActions>,
             &mut ActiveEntity<PlayerStates>,
             &mut KinematicCharacterController,
-            Option<&KinematicCharacterControllerOutput>,
+            &mut CharacterMotor,
+            &Speed,
+            &StatModifiers,
+            Option<&Stunned>,
+            Option<&Invulnerable>,
         ),
-        With<Player>,
+        (With<Player>, Without<Dead>),
     >,
     mut stop_jump: EventReader<StopJump>,
     mut sliding: EventReader<SlideEvent>,
+    mut bounce: EventReader<BounceEvent>,
 ) {
-    let (action_state, mut player, mut controller, controller_output) =
-        controller_query.single_mut();
-
-    let grounded = match controller_output {
-        Some(out) => out.grounded,
-        None => false,
+    let Ok((
+        p_entity,
+        action_state,
+        mut player,
+        mut controller,
+        mut motor,
+        speed,
+        modifiers,
+        stun,
+        invulnerable,
+    )) = controller_query.get_single_mut()
+    else {
+        return;
     };
+    let stunned = stun.is_some();
+    let grounded = motor.grounded;
+
+    jump_info.buffer.tick(time.delta());
+    jump_info.coyote_time.tick(time.delta());
+    // walked off the ledge without a jump, the ground jump is gone after coyote time
+    if jump_info.coyote_time.just_finished() && jump_info.count == 0 {
+        jump_info.count = 1;
+    }
 
-    let dt = time.delta_seconds();
-
-    // TODO: global variable
-    let speed = 58.0;
+    let speed = modifiers.apply(StatType::MoveSpeed, speed.0);
     let jump_impulse = 1000.0;
-    let mut instant_acceleration = Vec2::ZERO;
-    let mut instant_velocity = player.velocity;
 
-    let axis_vector = action_state
+    let mut axis_vector = action_state
         .clamped_axis_pair(PlayerActions::Move)
         .unwrap_or_default()
         .x();
+    if stunned {
+        axis_vector = 0.;
+    }
     if axis_vector != 0. {
         player.rotation = if (axis_vector * 9.) < 0. { -1 } else { 1 };
     }
 
-    // physics simulation
-    if grounded {
-        // friction
-        instant_velocity.x *= 0.9;
-    } else {
-        // friction in jump
-        instant_velocity.x *= 0.9;
-        // gravity
-        if !grounded {
-            instant_acceleration += Vec2::Y * rapier_config.gravity;
-
-            if !sliding.is_empty() {
-                let mut pos_slide = WallPosition::default();
-                for e in sliding.iter() {
-                    pos_slide = e.0.clone();
-                }
+    // friction and gravity are applied by the motor
+    if !grounded && !sliding.is_empty() {
+        let mut pos_slide = WallPosition::default();
+        for e in sliding.iter() {
+            pos_slide = e.0.clone();
+        }
 
-                let pos_slide = match pos_slide {
-                    WallPosition::Left => -1.,
-                    WallPosition::Right => 1.,
-                };
-                if pos_slide == axis_vector {
-                    instant_velocity.y = 1.; // reset inertia
-                    jump_info.count = 1; // TODO: implement method for count jumps
-                                         // info!("|||||||||||||||||||||||SLIDING|||||||||||||||||||||||");
-                    let slide_scale = -21.;
-                    instant_acceleration += Vec2::Y * slide_scale;
-                    sliding.clear();
-                }
-            }
+        let pos_slide = match pos_slide {
+            WallPosition::Left => -1.,
+            WallPosition::Right => 1.,
+        };
+        if pos_slide == axis_vector {
+            motor.velocity.y = 0.; // reset inertia
+            jump_info.count = 1; // TODO: implement method for count jumps
+                                 // info!("|||||||||||||||||||||||SLIDING|||||||||||||||||||||||");
+            let slide_scale = -21.;
+            motor.acceleration += Vec2::Y * slide_scale;
+            sliding.clear();
         }
     }
-    let mut y = 0.;
     for action in action_state.get_just_pressed() {
+        if stunned {
+            break;
+        }
         match action {
             PlayerActions::Jump => {
-                if jump_info.count >= MAX_JUMP {
-                    continue;
-                }
-                // instant_acceleration.y = 1.;
-                instant_velocity.y = 1.;
-                y = jump_impulse;
-                jump_info.time_up.reset();
-
-                jump_info.count += 1;
-                player.current_state = PlayerStates::Jump;
-
-                dash_info.is_used = false;
+                jump_info.buffer.reset();
             }
-            PlayerActions::Dash => {
-                if dash_info.cooldown_time.finished() {
-                    dash_info.evade_time.reset();
-                    dash_info.frames_count = 0;
-                    dash_info.is_used = true;
+            PlayerActions::Dash if dash_info.cooldown_time.finished() && !dash_info.is_used => {
+                dash_info.evade_time.reset();
+                dash_info.frames_count = 0;
+                dash_info.is_used = true;
+                // i-frames for the evade_time, don't cut longer invulnerability after hit
+                let evade = dash_info.evade_time.duration();
+                let remaining = invulnerable
+                    .map(|i| i.timer.remaining())
+                    .unwrap_or_default();
+                if remaining < evade {
+                    commands
+                        .entity(p_entity)
+                        .insert(Invulnerable::new(evade, false));
                 }
             }
             _ => (),
         }
     }
+    // pressed jump waits in the buffer until a jump is available
+    if !stunned && !jump_info.buffer.finished() && jump_info.count < MAX_JUMP {
+        jump_info.clear_buffer();
+        motor.jump(jump_impulse);
+        jump_info.time_up.reset();
+
+        jump_info.count += 1;
+        player.current_state = PlayerStates::Jump;
+
+        dash_info.finish();
+    }
 
     // info!("{:?}", dash_info);
 
     if !dash_info.cooldown_time.finished() {
         dash_info.cooldown_time.tick(time.delta());
     }
+    // pogo from the down attack
+    if bounce.iter().any(|e| e.entity == p_entity) {
+        motor.jump(POGO_IMPULSE);
+        jump_info.count = 0;
+        jump_info.time_up.reset();
+        player.current_state = PlayerStates::Jump;
+        dash_info.finish();
+    }
     if !stop_jump.is_empty() {
         stop_jump.clear();
         jump_info.time_up.tick(Duration::from_secs(1));
-        instant_velocity.y = 1.;
+        motor.velocity.y = 0.;
     }
     if player.current_state == PlayerStates::Jump {
         jump_info.time_up.tick(time.delta());
@@ -383,6 +523,7 @@ fn move_player(
     } else {
         if grounded {
             jump_info.count = 0;
+            jump_info.coyote_time.reset();
             if axis_vector == 0. {
                 player.current_state = PlayerStates::Idle;
             } else {
@@ -395,90 +536,164 @@ fn move_player(
 
     if dash_info.is_used {
         dash_info.evade_time.tick(time.delta());
-        instant_velocity.x *= DASH_SPEED_FACTOR;
+        motor.velocity.x *= DASH_SPEED_FACTOR;
         dash_info.frames_count += 1;
         if dash_info.frames_count == DASH_FRAMES {
-            dash_info.is_used = false;
-            dash_info.cooldown_time.reset();
+            dash_info.finish();
         }
         controller.translation = Some(Vec2::new(player.rotation as f32 * 35., 0.));
     }
 
-    instant_velocity += Vec2::new(axis_vector * speed, y);
-    instant_velocity = instant_velocity.clamp(Vec2::splat(-1000.0), Vec2::splat(1000.0));
-    player.velocity = (instant_acceleration * dt) + instant_velocity;
-    let translation = controller.translation.unwrap_or(Vec2::new(0., 0.)) + player.velocity * dt;
-    controller.translation = Some(translation);
+    motor.input.x += axis_vector * speed;
 }
 
 fn player_attack(
     mut commands: Commands,
     time: Res<Time>,
+    rapier_config: Res<RapierConfiguration>,
+    mut reload: Local<Timer>,
     mut controller_query: Query<
         (
             Entity,
             &ActionState<PlayerActions>,
             &mut AttackCollider, // maybe create local resource with entity collider
-            &Transform,
             &ActiveEntity<PlayerStates>,
-            &mut KinematicCharacterController,
+            &Weapon,
+            Option<&RangedWeapon>,
+            &mut Combo,
+            &Transform,
+            Option<&Stunned>,
             Option<&KinematicCharacterControllerOutput>,
         ),
-        With<Player>,
+        (With<Player>, Without<Dead>),
     >,
-    mut time_attack: Local<TimeAttack>, //TODO: attach to weapon
 ) {
-    let (p_entity, action_state, mut attack_collider, _, player, _, _) =
-        controller_query.single_mut();
+    let Ok((
+        p_entity,
+        action_state,
+        mut attack_collider,
+        player,
+        weapon,
+        bow,
+        mut combo,
+        transform,
+        stun,
+        controller_output,
+    )) = controller_query.get_single_mut()
+    else {
+        return;
+    };
+    reload.tick(time.delta());
     let is_attack = action_state.just_pressed(PlayerActions::Attack);
-    if let Some(collider_entity) = attack_collider.0 {
-        time_attack.0.tick(time.delta());
-        if time_attack.0.finished() {
-            commands
-                .entity(p_entity)
-                .remove_children(&[collider_entity]);
-            commands.entity(collider_entity).despawn();
-            attack_collider.0 = None;
+    // swing in progress, sensor removed by combat plugin
+    if attack_collider.0.is_some() {
+        if is_attack {
+            combo.queued = true;
         }
         return;
     }
-    if !is_attack {
+    if stun.is_some() {
+        combo.step = 0;
+        combo.queued = false;
         return;
     }
-    time_attack.0.reset();
-    let entity = commands
-        .spawn((
-            TransformBundle::from_transform(Transform::from_xyz(
-                player.rotation as f32 * 14.,
-                0.,
-                0.,
-            )),
-            // TransformBundle::from_transform(t.clone()),
-            Collider::cuboid(5., 10.),
-            Sensor,
-            ActiveEvents::COLLISION_EVENTS, // .insert(ActiveEvents::COLLISION_EVENTS)
-        ))
-        .id();
+
+    // chain is reset when the input window is over
+    combo.window.tick(time.delta());
+    if combo.window.finished() {
+        combo.step = 0;
+    }
+    if !is_attack && !combo.queued {
+        return;
+    }
+    combo.queued = false;
+
+    let grounded = match controller_output {
+        Some(out) => out.grounded,
+        None => false,
+    };
+    let axis_y = action_state
+        .clamped_axis_pair(PlayerActions::Move)
+        .unwrap_or_default()
+        .y();
+    let direction = if axis_y > ATTACK_AXIS_DEADZONE {
+        AttackDirection::Up
+    } else if axis_y < -ATTACK_AXIS_DEADZONE && !grounded {
+        AttackDirection::Down
+    } else {
+        AttackDirection::Side
+    };
+
+    // bow replaces side swings, up and down attacks stay melee for the pogo
+    if let (Some(bow), AttackDirection::Side) = (bow, direction) {
+        if reload.finished() {
+            let origin = transform.translation.truncate();
+            spawn_projectile(
+                &mut commands,
+                p_entity,
+                Faction::Player,
+                bow,
+                origin,
+                origin + Vec2::X * player.rotation as f32 * BOW_RANGE,
+                rapier_config.gravity,
+            );
+            // shots are limited by the attack time of the melee weapon
+            *reload = Timer::new(weapon.attack_time, TimerMode::Once);
+        }
+        return;
+    }
+
+    // up and down attacks are single swings outside the combo chain
+    if direction != AttackDirection::Side {
+        combo.step = 0;
+    }
+    let attack = weapon.combo_step(combo.step);
+    let entity = spawn_hitbox(&mut commands, p_entity, &attack, player.rotation, direction);
     attack_collider.0 = Some(entity);
-    commands.entity(p_entity).add_child(entity);
+
+    if direction == AttackDirection::Side {
+        combo.step = match weapon.combo.len() {
+            0 => 0,
+            len => (combo.step + 1) % len,
+        };
+    }
+    combo.window = Timer::new(weapon.combo_window, TimerMode::Once);
 }
 
-fn sensor_event(mut collision_events: EventReader<CollisionEvent>) {
-    for collision_event in collision_events.iter() {
-        match collision_event {
-            CollisionEvent::Started(entity_event, sensor_entity, type_entity) => {
-                // for (enemy_entity, mut health_enemy, name_enemy) in q.iter_mut() {
-                info!("[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[\n");
-                info!("{:?}", entity_event);
-                info!("{:?}", sensor_entity);
-                info!("{:?}", type_entity);
-                info!("]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]\n");
-                // }
-            }
-            CollisionEvent::Stopped(_, _, _) => (),
+fn player_death(
+    mut commands: Commands,
+    settings: Res<DeathSettings>,
+    mut death_events: EventReader<DeathEvent>,
+    mut player_q: Query<(&mut ActiveEntity<PlayerStates>, &mut Sprite), With<Player>>,
+) {
+    for event in death_events.iter() {
+        let Ok((mut player, mut sprite)) = player_q.get_mut(event.entity) else {
+            continue;
+        };
+        player.current_state = PlayerStates::Death;
+        sprite.color = Color::GRAY;
+        commands
+            .entity(event.entity)
+            .insert(GameOverTimer(Timer::new(
+                settings.game_over_time,
+                TimerMode::Once,
+            )));
+    }
+}
+
+fn game_over(
+    time: Res<Time>,
+    mut timer_q: Query<&mut GameOverTimer, With<Player>>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    for mut timer in timer_q.iter_mut() {
+        timer.0.tick(time.delta());
+        if timer.0.just_finished() {
+            state.set(GameState::GameOver);
         }
     }
 }
+
 // TODO: camera.rs for 1 function ?
 fn follow(
     time: Res<Time>,
@@ -0,0 +1,111 @@
+use crate::entities::RunEntity;
+use crate::ui::menu::ButtonColors;
+use crate::GameState;
+use bevy::prelude::*;
+
+pub struct GameOverPlugin;
+
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::GameOver),
+            (cleanup_world, setup_game_over).chain(),
+        )
+        .add_systems(
+            Update,
+            click_menu_button.run_if(in_state(GameState::GameOver)),
+        )
+        .add_systems(OnExit(GameState::GameOver), cleanup_game_over);
+    }
+}
+
+#[derive(Clone, Default, Debug, Component)]
+struct GameOverUi;
+
+// remove everything spawned by the run, next run spawns the world again
+fn cleanup_world(mut commands: Commands, q: Query<Entity, With<RunEntity>>) {
+    for entity in q.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn setup_game_over(mut commands: Commands, button_colors: Res<ButtonColors>) {
+    commands.spawn((Camera2dBundle::default(), GameOverUi));
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            GameOverUi,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Game Over",
+                TextStyle {
+                    font_size: 80.0,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                    ..default()
+                },
+            ));
+            parent
+                .spawn(ButtonBundle {
+                    style: Style {
+                        width: Val::Px(120.0),
+                        height: Val::Px(50.0),
+                        margin: UiRect::top(Val::Px(40.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    background_color: button_colors.normal.into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Menu",
+                        TextStyle {
+                            font_size: 40.0,
+                            color: Color::rgb(0.9, 0.9, 0.9),
+                            ..default()
+                        },
+                    ));
+                });
+        });
+}
+
+fn click_menu_button(
+    button_colors: Res<ButtonColors>,
+    mut state: ResMut<NextState<GameState>>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<Button>),
+    >,
+) {
+    for (interaction, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                state.set(GameState::Menu);
+            }
+            Interaction::Hovered => {
+                *color = button_colors.hovered.into();
+            }
+            Interaction::None => {
+                *color = button_colors.normal.into();
+            }
+        }
+    }
+}
+
+fn cleanup_game_over(mut commands: Commands, q: Query<Entity, With<GameOverUi>>) {
+    for entity in q.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
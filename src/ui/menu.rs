@@ -1,3 +1,4 @@
+use crate::player::class::{PlayerClass, SelectedClass};
 use crate::GameState;
 use bevy::prelude::*;
 
@@ -7,15 +8,15 @@ impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<ButtonColors>()
             .add_systems(OnEnter(GameState::Menu), setup_menu)
-            .add_systems(Update, click_play_button.run_if(in_state(GameState::Menu)))
+            .add_systems(Update, click_class_button.run_if(in_state(GameState::Menu)))
             .add_systems(OnExit(GameState::Menu), cleanup_menu);
     }
 }
 
 #[derive(Resource)]
-struct ButtonColors {
-    normal: Color,
-    hovered: Color,
+pub struct ButtonColors {
+    pub normal: Color,
+    pub hovered: Color,
 }
 
 impl Default for ButtonColors {
@@ -27,44 +28,74 @@ impl Default for ButtonColors {
     }
 }
 
+#[derive(Clone, Default, Debug, Component)]
+struct MenuUi;
+
+// button starting the run with the class
+#[derive(Clone, Default, Debug, Component)]
+struct ClassButton(PlayerClass);
+
 fn setup_menu(mut commands: Commands, button_colors: Res<ButtonColors>) {
-    commands.spawn(Camera2dBundle::default());
+    commands.spawn((Camera2dBundle::default(), MenuUi));
     commands
-        .spawn(ButtonBundle {
-            style: Style {
-                width: Val::Px(120.0),
-                height: Val::Px(50.0),
-                margin: UiRect::all(Val::Auto),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
                 ..Default::default()
             },
-            background_color: button_colors.normal.into(),
-            ..Default::default()
-        })
+            MenuUi,
+        ))
         .with_children(|parent| {
-            parent.spawn(TextBundle::from_section(
-                "Play",
-                TextStyle {
-                    font_size: 40.0,
-                    color: Color::rgb(0.9, 0.9, 0.9),
-                    ..default()
-                },
-            ));
+            for class in PlayerClass::ALL {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(180.0),
+                                height: Val::Px(50.0),
+                                margin: UiRect::all(Val::Px(10.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..Default::default()
+                            },
+                            background_color: button_colors.normal.into(),
+                            ..Default::default()
+                        },
+                        ClassButton(class),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            class.definition().name,
+                            TextStyle {
+                                font_size: 40.0,
+                                color: Color::rgb(0.9, 0.9, 0.9),
+                                ..default()
+                            },
+                        ));
+                    });
+            }
         });
 }
 
-fn click_play_button(
+fn click_class_button(
+    mut commands: Commands,
     button_colors: Res<ButtonColors>,
     mut state: ResMut<NextState<GameState>>,
     mut interaction_query: Query<
-        (&Interaction, &mut BackgroundColor),
+        (&Interaction, &mut BackgroundColor, &ClassButton),
         (Changed<Interaction>, With<Button>),
     >,
 ) {
-    for (interaction, mut color) in &mut interaction_query {
+    for (interaction, mut color, button) in &mut interaction_query {
         match *interaction {
             Interaction::Pressed => {
+                commands.insert_resource(SelectedClass(button.0));
                 state.set(GameState::InGame);
             }
             Interaction::Hovered => {
@@ -77,11 +108,8 @@ fn click_play_button(
     }
 }
 
-fn cleanup_menu(
-    mut commands: Commands,
-    button: Query<Entity, With<Button>>,
-    camera: Query<Entity, With<Camera2d>>,
-) {
-    commands.entity(button.single()).despawn_recursive();
-    commands.entity(camera.single()).despawn_recursive();
+fn cleanup_menu(mut commands: Commands, q: Query<Entity, With<MenuUi>>) {
+    for entity in q.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
 }
@@ -1,3 +1,4 @@
+pub mod game_over;
 pub mod menu;
 pub mod pause;
 pub mod settings;
@@ -0,0 +1,113 @@
+use crate::entities::*;
+use crate::events::EquipWeaponEvent;
+use crate::loading::WeaponAssets;
+use crate::GameState;
+
+use bevy::reflect::{TypePath, TypeUuid};
+use bevy::{prelude::*, utils::Duration};
+use serde::Deserialize;
+
+pub struct WeaponPlugin;
+
+impl Plugin for WeaponPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<EquipWeaponEvent>()
+            .add_systems(Update, equip_weapon.run_if(in_state(GameState::InGame)));
+    }
+}
+
+// weapon loaded from assets/weapons/*.weapon.ron, enemy archetypes write the same fields inline
+// same fields as Weapon, time in milliseconds, combo - empty for a single swing
+#[derive(Deserialize, TypeUuid, TypePath, Debug, Clone)]
+#[uuid = "b3e2d4a6-5c1f-4f8e-8a27-9d0c6e1b7f42"]
+pub struct WeaponDefinition {
+    pub name: String,
+    pub damage: isize,
+    pub attack_ms: u64,
+    pub hitbox: (f32, f32),
+    pub offset: (f32, f32),
+    #[serde(default)]
+    pub interrupt: isize,
+    #[serde(default)]
+    pub knockback: f32,
+    #[serde(default)]
+    pub effects: Vec<DoTType>,
+    #[serde(default)]
+    pub combo: Vec<ComboDefinition>,
+    #[serde(default)]
+    pub combo_window_ms: u64,
+}
+
+// same fields as ComboStep
+#[derive(Deserialize, Debug, Clone)]
+pub struct ComboDefinition {
+    pub damage_multiplier: f32,
+    pub attack_ms: u64,
+    pub hitbox: (f32, f32),
+    pub offset: (f32, f32),
+}
+
+impl WeaponDefinition {
+    pub fn weapon(&self) -> Weapon {
+        Weapon {
+            name: self.name.clone(),
+            damage: Damage(self.damage),
+            attack_time: Duration::from_millis(self.attack_ms),
+            hitbox: Vec2::new(self.hitbox.0, self.hitbox.1),
+            offset: Vec2::new(self.offset.0, self.offset.1),
+            interrupt: self.interrupt,
+            knockback: self.knockback,
+            effects: self
+                .effects
+                .iter()
+                .map(|t| DoTStat::from_type(*t))
+                .collect(),
+            combo: self
+                .combo
+                .iter()
+                .map(|step| {
+                    ComboStep::new(
+                        step.damage_multiplier,
+                        step.attack_ms,
+                        Vec2::new(step.hitbox.0, step.hitbox.1),
+                        Vec2::new(step.offset.0, step.offset.1),
+                    )
+                })
+                .collect(),
+            combo_window: Duration::from_millis(self.combo_window_ms),
+        }
+    }
+}
+
+impl WeaponAssets {
+    // loaded weapon with the name, None if there is no such file
+    pub fn weapon(&self, definitions: &Assets<WeaponDefinition>, name: &str) -> Option<Weapon> {
+        self.weapons
+            .iter()
+            .filter_map(|handle| definitions.get(handle))
+            .find(|definition| definition.name == name)
+            .map(|definition| definition.weapon())
+    }
+}
+
+// weapon of the event replaces the equipped one, the combo chain starts again
+fn equip_weapon(
+    mut equip_events: EventReader<EquipWeaponEvent>,
+    weapon_assets: Res<WeaponAssets>,
+    definitions: Res<Assets<WeaponDefinition>>,
+    mut holder_q: Query<(&mut Weapon, Option<&mut Combo>)>,
+) {
+    for event in equip_events.iter() {
+        let Ok((mut weapon, combo)) = holder_q.get_mut(event.entity) else {
+            continue;
+        };
+        let Some(equipped) = weapon_assets.weapon(&definitions, &event.weapon) else {
+            warn!("weapon {} is not in assets/weapons", event.weapon);
+            continue;
+        };
+        *weapon = equipped;
+        if let Some(mut combo) = combo {
+            *combo = Combo::default();
+        }
+    }
+}